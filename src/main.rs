@@ -1,8 +1,8 @@
 use std::env;
 
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use tokio::io::{self, AsyncBufReadExt, BufReader};
-use tumblr_api::{oauth::OAuthCredentials, AuthenticatedTumblrClient, TumblrClient};
+use tumblr_api::{oauth::OAuthCredentials, Client, Temporary};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -16,24 +16,23 @@ async fn main() -> anyhow::Result<()> {
 	let oauth_token = env::var("TUMBLR_OAUTH_TOKEN").ok();
 	let oauth_token_secret = env::var("TUMBLR_OAUTH_TOKEN_SECRET").ok();
 
+	let client = Client::new(oauth_consumer_key, oauth_client_secret);
+
 	let client = {
 		if let (Some(oauth_token), Some(oauth_token_secret)) = (oauth_token, oauth_token_secret) {
-			AuthenticatedTumblrClient::from_credentials(
-				oauth_consumer_key,
-				oauth_client_secret,
-				OAuthCredentials {
+			client
+				.with_credentials(OAuthCredentials {
 					oauth_token,
 					oauth_token_secret,
-				},
-			)?
+				})
+				.map_err(|_| anyhow!("Client is shared"))?
 		} else {
-			let client = TumblrClient::create(oauth_consumer_key, oauth_client_secret)?;
-
-			let credentials = client.create_temporary_credentials().await?;
+			let client = client
+				.try_into_temporary()
+				.await?
+				.map_err(|_| anyhow!("Client is shared"))?;
 
-			println!("{:?}", credentials);
-
-			println!("{}", client.generate_callback_url(&credentials));
+			println!("{}", client.generate_callback_url());
 
 			let mut url = String::new();
 
@@ -41,13 +40,17 @@ async fn main() -> anyhow::Result<()> {
 
 			reader.read_line(&mut url).await?;
 
-			let oauth_verifier = client
-				.parse_redirect_url(&url[..url.find("\n").unwrap()])
-				.unwrap();
-			client.verify_token(credentials, oauth_verifier).await?
+			let oauth_verifier = Client::<Temporary>::parse_redirect_url(url.trim())?
+				.context("Missing oauth_verifier in redirect URL")?;
+			client
+				.verify_token(oauth_verifier)
+				.await?
+				.map_err(|_| anyhow!("Client is shared"))?
 		}
 	};
 
+	println!("{:?}", client);
+
 	println!("\n\n\n\n\n");
 
 	Ok(())
@@ -1,8 +1,21 @@
-use serde::Deserialize;
+use std::fmt;
 
-use crate::{post::Post, AuthenticatedTumblrClient};
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use reqwest::Method;
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 
-#[derive(Deserialize, Debug)]
+use crate::{
+	oauth::oauth_encode,
+	post::{
+		CreatedPost, FetchedPost, LenientPostsPage, LikesPage, NotesPage, PostState, PostsPage,
+		Tag, TrailMode,
+	},
+	upload::MediaFile,
+	check_limit, to_json, with_query, Authenticated, Client, Error, MAX_LIMIT,
+};
+
+#[derive(Deserialize, Debug, Clone)]
 pub struct BlogInfo {
 	pub title: String,
 	pub posts: u64,
@@ -16,47 +29,1754 @@ pub struct BlogInfo {
 	pub ask_anon: Option<bool>,
 	/// only if blog is primary blog and sharing likes is enabled
 	pub likes: Option<u8>,
+	/// the blog is flagged as explicit.  Clients that filter mature content should hide it
+	pub is_nsfw: Option<bool>,
+	/// the blog's owner marked it as adult content, see [`BlogInfo::is_nsfw`]
+	pub is_adult: Option<bool>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct AuthedBlogInfo {
 	#[serde(flatten)]
 	pub info: BlogInfo,
 	pub is_blocked_from_primary: Option<bool>,
+	/// whether the authenticated user follows the blog
+	pub followed: Option<bool>,
+	/// only sent if the blog shows its follower count, or the user is a member of it
+	pub followers: Option<u64>,
 	// TODO avatar
-	// TODO theme
+	/// only sent if the user is a member of the blog
+	pub theme: Option<BlogTheme>,
 	/// timezone as location string available only if user is a member of this blog
 	pub timezone: Option<String>,
 	/// timezone as offset from UTC, see above
 	pub timezpme_offset: Option<String>,
 }
 
-#[derive(Debug)]
-pub struct BlogClient<'a> {
-	client: &'a AuthenticatedTumblrClient,
-	blog_identifier: &'a str,
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AvatarShape {
+	Circle,
+	Square,
+	#[serde(other)]
+	Other,
 }
 
-impl<'a> BlogClient<'a> {
-	pub(crate) fn new(client: &'a AuthenticatedTumblrClient, blog_identifier: &'a str) -> Self {
-		Self {
-			client,
-			blog_identifier,
+/// The blog's theme, as in [`AuthedBlogInfo::theme`].
+/// For [`Blog::update_theme`], only the fields that are `Some` are changed
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct BlogTheme {
+	pub avatar_shape: Option<AvatarShape>,
+	/// e.g. `#FAFAFA`
+	pub background_color: Option<String>,
+	pub body_font: Option<String>,
+	pub header_image: Option<String>,
+	pub header_stretch: Option<bool>,
+	pub link_color: Option<String>,
+	pub show_avatar: Option<bool>,
+	pub show_description: Option<bool>,
+	pub show_header_image: Option<bool>,
+	pub show_title: Option<bool>,
+	pub title_color: Option<String>,
+	pub title_font: Option<String>,
+	/// `bold` or `regular`
+	pub title_font_weight: Option<String>,
+}
+
+/// The largest avatar [`Blog::set_avatar`] uploads, in bytes
+pub const MAX_AVATAR_SIZE: usize = 10 * 1024 * 1024;
+
+#[derive(Deserialize)]
+pub(crate) struct BlogInfoResponse<B> {
+	pub(crate) blog: B,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct SearchOptions {
+	/// number of results to return, 1-20
+	pub limit: Option<u32>,
+	pub offset: Option<u64>,
+}
+
+impl SearchOptions {
+	/// Returns [`Error::Validation`] if `limit` is out of range.  Called before every request
+	pub fn validate(&self) -> crate::Result<()> {
+		check_limit(self.limit)
+	}
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct GetPostsOptions {
+	/// only posts with this tag
+	pub tag: Option<Tag>,
+	/// number of results to return, 1-20
+	pub limit: Option<u32>,
+	pub offset: Option<u64>,
+	/// only posts published before this time, in seconds from epoch
+	pub before: Option<u64>,
+	/// include where each post was reblogged from, see [`FetchedPost::reblog_info`](crate::post::FetchedPost::reblog_info)
+	pub reblog_info: Option<bool>,
+	/// include each post's most recent notes, see [`FetchedPost::notes`](crate::post::FetchedPost::notes)
+	pub notes_info: Option<bool>,
+	pub trail_mode: Option<TrailMode>,
+	/// Only honored by some listings, which otherwise return posts newest first regardless.
+	/// To be sure of the order, sort the fetched page with e.g. [`FetchedPost::by_timestamp`](crate::post::FetchedPost::by_timestamp)
+	pub sort: Option<PostSort>,
+}
+
+/// The order of posts in a listing, see [`GetPostsOptions::sort`]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PostSort {
+	/// Tumblr's default
+	Newest,
+	Oldest,
+}
+
+impl GetPostsOptions {
+	/// Returns [`Error::Validation`] if `limit` is out of range.  Called before every request.
+	/// `offset` isn't capped, since blog archives can be paged all the way back
+	pub fn validate(&self) -> crate::Result<()> {
+		check_limit(self.limit)
+	}
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct QueueOptions {
+	/// number of results to return, 1-20
+	pub limit: Option<u32>,
+	pub offset: Option<u64>,
+}
+
+impl QueueOptions {
+	/// Returns [`Error::Validation`] if `limit` is out of range.  Called before every request
+	pub fn validate(&self) -> crate::Result<()> {
+		check_limit(self.limit)
+	}
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct LikesOptions {
+	/// number of results to return, 1-20
+	pub limit: Option<u32>,
+	/// can't be combined with `before` or `after`
+	pub offset: Option<u64>,
+	/// only posts liked before this time, in seconds from epoch
+	pub before: Option<u64>,
+	/// only posts liked after this time, in seconds from epoch
+	pub after: Option<u64>,
+}
+
+impl LikesOptions {
+	/// Returns [`Error::Validation`] if `limit` is out of range.  Called before every request
+	pub fn validate(&self) -> crate::Result<()> {
+		check_limit(self.limit)
+	}
+}
+
+/// Which notes [`Blog::notes`] returns
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotesMode {
+	/// every note, newest first.  Tumblr's default
+	All,
+	Likes,
+	/// replies and reblogs with comments
+	Conversation,
+	/// replies and reblogs with comments as [`NotesPage::notes`], the rest summarized in [`NotesPage::rollup_notes`]
+	/// and the `total_*` counts, for "X and Y reblogged this" summaries
+	Rollup,
+	ReblogsWithTags,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct NotesOptions {
+	pub mode: Option<NotesMode>,
+	/// only notes from before this time, in seconds from epoch
+	pub before_timestamp: Option<u64>,
+}
+
+/// The ways the API accepts to identify a blog
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BlogIdentifier {
+	/// the blog's short name, e.g. `staff`
+	Name(String),
+	/// the blog's hostname, e.g. `staff.tumblr.com` or a custom domain
+	Hostname(String),
+	/// the blog's UUID, including the `t:` prefix
+	Uuid(String),
+}
+
+impl BlogIdentifier {
+	/// Detects which form `blog_identifier` is in
+	pub fn parse(blog_identifier: &str) -> Self {
+		let blog_identifier = blog_identifier.trim();
+
+		if blog_identifier.starts_with("t:") {
+			Self::Uuid(blog_identifier.to_owned())
+		} else if blog_identifier.contains('.') {
+			Self::Hostname(blog_identifier.to_ascii_lowercase())
+		} else {
+			Self::Name(blog_identifier.to_owned())
 		}
 	}
 
+	/// The identifier as it goes in a `blog/{blog_identifier}/...` path
+	pub fn to_path_segment(&self) -> String {
+		self.to_string()
+	}
+}
+
+/// Displays the path segment, see [`BlogIdentifier::to_path_segment`]
+impl fmt::Display for BlogIdentifier {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Name(name) => f.write_str(name),
+			Self::Hostname(hostname) => f.write_str(hostname),
+			Self::Uuid(uuid) => f.write_str(uuid),
+		}
+	}
+}
+
+impl From<&str> for BlogIdentifier {
+	#[inline]
+	fn from(blog_identifier: &str) -> Self {
+		Self::parse(blog_identifier)
+	}
+}
+
+/// Cheap to clone, since the client is shared
+#[derive(Debug, Clone)]
+pub struct Blog {
+	client: Client<Authenticated>,
+	blog_identifier: BlogIdentifier,
+}
+
+impl Client<Authenticated> {
+	/// A handle for calling the endpoints of one blog.
+	/// Returns [`Error::Validation`] right away if `blog_identifier` is empty or only whitespace
+	pub fn blog(&self, blog_identifier: impl Into<BlogIdentifier>) -> crate::Result<Blog> {
+		let blog_identifier = blog_identifier.into();
+
+		if blog_identifier.to_path_segment().trim().is_empty() {
+			return Err(Error::Validation("blog identifier is empty".to_owned()));
+		}
+
+		Ok(Blog {
+			client: self.clone(),
+			blog_identifier,
+		})
+	}
+
+	/// Fetches the info of each of `blog_identifiers` with [`Blog::info`] (and its cache), running at most `concurrency` requests at once.
+	/// Results are in the same order as `blog_identifiers`, and one failing doesn't stop the rest
+	pub async fn blogs_info(
+		&self,
+		blog_identifiers: &[&str],
+		concurrency: usize,
+	) -> Vec<crate::Result<AuthedBlogInfo>> {
+		stream::iter(blog_identifiers)
+			.map(|&blog_identifier| async move { self.blog(blog_identifier)?.info().await })
+			.buffered(concurrency.max(1))
+			.collect()
+			.await
+	}
+}
+
+impl Blog {
+	#[inline]
+	pub fn identifier(&self) -> &BlogIdentifier {
+		&self.blog_identifier
+	}
+
+	/// `blog/{blog_identifier}/{endpoint}`
+	fn path(&self, endpoint: &str) -> String {
+		format!("blog/{}/{endpoint}", self.blog_identifier.to_path_segment())
+	}
+
+	/// Fetches the blog's info, or returns it from the client's cache if [`ClientBuilder::blog_info_cache`](crate::ClientBuilder::blog_info_cache) is enabled
 	pub async fn info(&self) -> crate::Result<AuthedBlogInfo> {
+		let cache = self.client.blog_info_cache.as_ref();
+
+		let key = self.blog_identifier.to_path_segment();
+
+		if let Some(info) = cache.and_then(|cache| cache.get(&key)) {
+			return Ok(info);
+		}
+
+		let BlogInfoResponse::<AuthedBlogInfo> { blog } =
+			self.client.get(&self.path("info")).await?;
+
+		if let Some(cache) = cache {
+			cache.insert(key, blog.clone());
+		}
+
+		Ok(blog)
+	}
+
+	/// Fetches only the given `fields` of the blog's info (e.g., `["name", "posts"]`), through `fields[blogs]`.
+	/// Returns the raw JSON, since it won't have every field of [`AuthedBlogInfo`], and bypasses the cache
+	pub async fn info_fields(&self, fields: &[&str]) -> crate::Result<serde_json::Value> {
+		let path = with_query(self.path("info"), &[("fields[blogs]", fields.join(","))])?;
+
+		let BlogInfoResponse { blog } = self.client.get(&path).await?;
+
+		Ok(blog)
+	}
+
+	/// The blog's total number of posts, from [`Blog::info`] (and its cache)
+	pub async fn post_count(&self) -> crate::Result<u64> {
+		self.info().await.map(|info| info.info.posts)
+	}
+
+	/// Searches the blog's posts for `query`.
+	/// Returns [`Error::SearchDisabled`] if the blog has turned off search
+	pub async fn search(&self, query: &str, options: &SearchOptions) -> crate::Result<PostsPage> {
+		options.validate()?;
+
+		let path = with_query(
+			self.path(&format!("search/{}", oauth_encode(query))),
+			options,
+		)?;
+
+		match self.client.get(&path).await {
+			Err(Error::Tumblr(meta)) if meta.status == 403 => Err(Error::SearchDisabled(
+				self.blog_identifier.to_path_segment(),
+			)),
+			other => other,
+		}
+	}
+
+	pub async fn get_posts(&self, options: &GetPostsOptions) -> crate::Result<PostsPage> {
+		options.validate()?;
+
 		self.client
-			.get(&format!("blog/{}/info", self.blog_identifier))
+			.get(&with_query(self.path("posts"), options)?)
 			.await
 	}
 
-	pub async fn get_posts<const limit: usize>(
+	/// Every post matching `options`, fetching pages of `options.limit` as the stream is polled.
+	/// Each page's `next` link is followed until a page doesn't have one, or has fewer posts than the limit.
+	/// The stream ends after the first error
+	pub fn posts_stream(
 		&self,
-		tags: Option<Vec<String>>,
-		offset: Option<u64>,
-		before: Option<u64>,
-	) -> crate::Result<[Post; limit]> {
-		todo!()
+		options: GetPostsOptions,
+	) -> impl Stream<Item = crate::Result<FetchedPost>> + '_ {
+		let limit = options.limit.unwrap_or(MAX_LIMIT) as usize;
+		let first = options
+			.validate()
+			.and_then(|()| with_query(self.path("posts"), &options));
+
+		stream::try_unfold(Some(first), move |path| async move {
+			let Some(path) = path else {
+				return Ok(None);
+			};
+
+			let page: PostsPage = self.client.get(&path?).await?;
+
+			let next = page
+				.links
+				.as_ref()
+				.and_then(|links| links.next.as_ref())
+				.filter(|_| page.len() >= limit)
+				.map(|next| with_query(self.path("posts"), &next.query_params));
+
+			Ok::<_, Error>(Some((stream::iter(page.posts.into_iter().map(Ok)), next)))
+		})
+		.try_flatten()
+	}
+
+	/// Writes every post on the blog to `writer` as newline-delimited JSON, newest first, and returns how many were written.
+	/// Pages by `before` the oldest timestamp seen so far, with `offset` only counting the posts at that timestamp,
+	/// so posts published or deleted during the export don't shift the pages.  Post ids only ever grow, so any post
+	/// at or above the last id written is skipped rather than written twice.
+	/// To resume an interrupted export, pass the `id` of the last line written as `resume_after`:
+	/// paging starts at that post's timestamp, so the posts already exported aren't fetched again.
+	/// Returns [`Error::Validation`] if the blog has no post `resume_after`
+	pub async fn export_ndjson<W: AsyncWrite + Unpin>(
+		&self,
+		mut writer: W,
+		resume_after: Option<u64>,
+	) -> crate::Result<u64> {
+		let mut options = GetPostsOptions {
+			limit: Some(MAX_LIMIT),
+			..Default::default()
+		};
+		let mut last_id = None;
+
+		if let Some(id) = resume_after {
+			let post = match self
+				.client
+				.get::<FetchedPost>(&self.path(&format!("posts/{id}")))
+				.await
+			{
+				Err(Error::Tumblr(meta)) if meta.status == 404 => Err(Error::Validation(format!(
+					"can't resume after post {id}, which isn't on the blog"
+				))),
+				other => other,
+			}?;
+
+			options.before = Some(post.timestamp + 1);
+			last_id = Some(id);
+		}
+
+		let mut previous_first = None;
+		let mut exported = 0;
+
+		loop {
+			let page = self.get_posts(&options).await?;
+
+			let (first, oldest) = match (page.posts.first(), page.posts.last()) {
+				(Some(first), Some(last)) => (first.id, last.timestamp),
+				_ => break,
+			};
+
+			// stop rather than loop forever if the API ignores `before` and `offset`
+			if previous_first == Some(first) {
+				break;
+			}
+			previous_first = Some(first);
+
+			for post in &page {
+				if last_id.is_some_and(|last_id| post.id >= last_id) {
+					continue;
+				}
+
+				let mut line = serde_json::to_vec(post).map_err(Error::Serialize)?;
+				line.push(b'\n');
+				writer.write_all(&line).await?;
+				exported += 1;
+				last_id = Some(post.id);
+			}
+
+			if options.before != Some(oldest + 1) {
+				options.before = Some(oldest + 1);
+				options.offset = None;
+			}
+			let same_second = page
+				.posts
+				.iter()
+				.filter(|post| post.timestamp == oldest)
+				.count();
+			options.offset = Some(options.offset.unwrap_or(0) + same_second as u64);
+		}
+
+		writer.flush().await?;
+
+		Ok(exported)
+	}
+
+	/// The posts the blog has liked, most recent first.
+	/// Returns [`Error::LikesNotShared`] if the blog keeps its likes private
+	pub async fn likes(&self, options: &LikesOptions) -> crate::Result<LikesPage> {
+		options.validate()?;
+
+		match self
+			.client
+			.get(&with_query(self.path("likes"), options)?)
+			.await
+		{
+			Err(Error::Tumblr(meta)) if meta.status == 403 => Err(Error::LikesNotShared(
+				self.blog_identifier.to_path_segment(),
+			)),
+			other => other,
+		}
+	}
+
+	/// The notes on the blog's post `post_id`, see [`NotesMode`] for which
+	pub async fn notes(&self, post_id: u64, options: &NotesOptions) -> crate::Result<NotesPage> {
+		self.client
+			.get(&with_query(
+				format!("{}?id={post_id}", self.path("notes")),
+				options,
+			)?)
+			.await
+	}
+
+	/// The blog's queued posts, in queue order.
+	/// See [`FetchedPost::scheduled_publish_time`](crate::post::FetchedPost::scheduled_publish_time) for when each goes live
+	pub async fn queue(&self, options: &QueueOptions) -> crate::Result<PostsPage> {
+		options.validate()?;
+
+		self.client
+			.get(&with_query(self.path("posts/queue"), options)?)
+			.await
+	}
+
+	/// Takes the queued post `post_id` out of the queue and moves it back to the blog's drafts
+	pub async fn unschedule(&self, post_id: u64) -> crate::Result<()> {
+		let body = serde_json::json!({ "id": post_id, "state": PostState::Draft });
+
+		self.client
+			.post_form::<CreatedPost>(&self.path("post/edit"), &body)
+			.await
+			.map(drop)
+	}
+
+	/// Deletes the post `post_id`, whether it's published, queued, or a draft
+	pub async fn delete_post(&self, post_id: u64) -> crate::Result<()> {
+		let body = serde_json::json!({ "id": post_id });
+
+		self.client
+			.post_form::<CreatedPost>(&self.path("post/delete"), &body)
+			.await
+			.map(drop)
+	}
+
+	/// Replaces the blog's avatar with `image`, uploaded as `multipart/form-data`.
+	/// Returns [`Error::Validation`] without uploading if `image` isn't a JPEG, PNG or GIF, or is empty or larger than [`MAX_AVATAR_SIZE`]
+	pub async fn set_avatar(&self, image: MediaFile) -> crate::Result<()> {
+		if !matches!(image.mime_type(), "image/jpeg" | "image/png" | "image/gif") {
+			return Err(Error::Validation(format!(
+				"an avatar can't be {}",
+				image.mime_type()
+			)));
+		}
+
+		if image.data().is_empty() || image.data().len() > MAX_AVATAR_SIZE {
+			return Err(Error::Validation(format!(
+				"an avatar must be between 1 and {MAX_AVATAR_SIZE} bytes, not {}",
+				image.data().len()
+			)));
+		}
+
+		// the endpoint expects the image as the `data` part
+		self.client
+			.send_multipart::<IgnoredAny>(
+				Method::POST,
+				&self.path("avatar"),
+				None,
+				&[image.with_identifier("data")],
+			)
+			.await
+			.map(drop)
+	}
+
+	/// Changes the fields of the blog's theme that are `Some` in `theme`
+	pub async fn update_theme(&self, theme: &BlogTheme) -> crate::Result<()> {
+		self.client
+			.post::<IgnoredAny>(&self.path("theme"), Some(&to_json(theme)?))
+			.await
+			.map(drop)
+	}
+
+	/// Like [`Blog::get_posts`], but a post that can't be parsed is returned as an `Err` in its place instead of failing the whole page
+	pub async fn get_posts_lenient(
+		&self,
+		options: &GetPostsOptions,
+	) -> crate::Result<LenientPostsPage> {
+		options.validate()?;
+
+		self.client
+			.get(&with_query(self.path("posts"), options)?)
+			.await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use wiremock::{
+		matchers::{
+			body_json, body_string, header, method, path, query_param, query_param_is_missing,
+		},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	use super::*;
+	use crate::{
+		client::tests::{authenticated, mock_builder, mock_client, ok_response},
+		oauth::tests::assert_valid_signature,
+		post::NoteType,
+		FetchPostOptions, TaggedOptions,
+	};
+
+	#[test]
+	fn test_parse_blog_identifier() {
+		assert_eq!(
+			BlogIdentifier::parse("staff"),
+			BlogIdentifier::Name("staff".to_owned())
+		);
+		assert_eq!(
+			BlogIdentifier::parse("Staff.Tumblr.com"),
+			BlogIdentifier::Hostname("staff.tumblr.com".to_owned())
+		);
+		assert_eq!(
+			BlogIdentifier::parse("t:123456abcdf"),
+			BlogIdentifier::Uuid("t:123456abcdf".to_owned())
+		);
+
+		assert_eq!(BlogIdentifier::parse(" staff ").to_path_segment(), "staff");
+		assert_eq!(
+			BlogIdentifier::parse("davidslog.com").to_path_segment(),
+			"davidslog.com"
+		);
+		assert_eq!(
+			BlogIdentifier::parse("t:123456abcdf").to_path_segment(),
+			"t:123456abcdf"
+		);
+	}
+
+	#[tokio::test]
+	async fn test_blog_paths() {
+		let server = MockServer::start().await;
+
+		for segment in ["staff", "staff.tumblr.com", "t:0aY0xL2Fi1OFJg4YxpmegQ"] {
+			Mock::given(method("GET"))
+				.and(path(format!("/blog/{segment}/info")))
+				.respond_with(ok_response(STAFF_INFO))
+				.expect(1)
+				.mount(&server)
+				.await;
+		}
+
+		let client = mock_client(&server);
+
+		for (blog_identifier, expected) in [
+			(" staff", BlogIdentifier::Name("staff".to_owned())),
+			(
+				"Staff.Tumblr.com",
+				BlogIdentifier::Hostname("staff.tumblr.com".to_owned()),
+			),
+			(
+				"t:0aY0xL2Fi1OFJg4YxpmegQ",
+				BlogIdentifier::Uuid("t:0aY0xL2Fi1OFJg4YxpmegQ".to_owned()),
+			),
+		] {
+			let blog = client.blog(blog_identifier).unwrap();
+			assert_eq!(blog.identifier(), &expected);
+			assert_eq!(expected.to_string(), expected.to_path_segment());
+
+			blog.info().await.unwrap();
+		}
+
+		let requests = server.received_requests().await.unwrap();
+		let paths: Vec<_> = requests
+			.iter()
+			.map(|request| request.url.path().to_owned())
+			.collect();
+
+		// the UUID's `:` is valid in a path, so it isn't percent-encoded
+		assert_eq!(
+			paths,
+			[
+				"/blog/staff/info",
+				"/blog/staff.tumblr.com/info",
+				"/blog/t:0aY0xL2Fi1OFJg4YxpmegQ/info",
+			]
+		);
+
+		// the UUID is mixed-case, so this catches a signature over a case-folded path
+		for request in &requests {
+			assert_valid_signature(&server, request);
+		}
+	}
+
+	const STAFF_INFO: &str = r#"{
+	"blog": {
+		"title": "Tumblr Staff",
+		"posts": 6500,
+		"name": "staff",
+		"updated": 1654041600,
+		"description": "The official Tumblr staff blog",
+		"ask": true,
+		"ask_anon": false,
+		"timezone": "US/Eastern",
+		"timezone_offset": "-05:00"
+	}
+}"#;
+
+	#[tokio::test]
+	async fn test_info_cache_shared_between_blogs() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.respond_with(ok_response(STAFF_INFO))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let client = authenticated(
+			mock_builder(&server).blog_info_cache(8, std::time::Duration::from_secs(60)),
+		);
+
+		let first = client.blog("staff").unwrap().info().await.unwrap();
+		let second = client.clone().blog("staff").unwrap().info().await.unwrap();
+
+		assert_eq!(first.info.name, "staff");
+		assert_eq!(second.info.posts, 6500);
+	}
+
+	#[tokio::test]
+	async fn test_cloned_blog() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.respond_with(ok_response(STAFF_INFO))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let blog = mock_client(&server).blog("staff").unwrap();
+		let clone = blog.clone();
+		drop(blog);
+
+		let info = tokio::spawn(async move { clone.info().await })
+			.await
+			.unwrap()
+			.unwrap();
+
+		assert_eq!(info.info.name, "staff");
+	}
+
+	#[test]
+	fn test_blog_rejects_empty_identifier() {
+		let client = authenticated(Client::builder(
+			"consumer-key".to_owned(),
+			"client-secret".to_owned(),
+		));
+
+		assert!(matches!(client.blog(""), Err(Error::Validation(_))));
+		assert!(matches!(client.blog("  \t"), Err(Error::Validation(_))));
+		assert_eq!(
+			client.blog("staff").unwrap().identifier(),
+			&BlogIdentifier::Name("staff".to_owned())
+		);
+	}
+
+	#[tokio::test]
+	async fn test_blogs_info() {
+		let server = MockServer::start().await;
+
+		for name in ["staff", "engineering"] {
+			Mock::given(method("GET"))
+				.and(path(format!("/blog/{name}/info")))
+				.respond_with(
+					ok_response(&STAFF_INFO.replace(r#""staff""#, &format!("{name:?}")))
+						.set_delay(std::time::Duration::from_millis(100)),
+				)
+				.expect(1)
+				.mount(&server)
+				.await;
+		}
+
+		Mock::given(method("GET"))
+			.and(path("/blog/deleted/info"))
+			.respond_with(
+				ResponseTemplate::new(404)
+					.set_body_raw(
+						r#"{"meta":{"status":404,"msg":"Not Found"},"response":[]}"#,
+						"application/json",
+					)
+					.set_delay(std::time::Duration::from_millis(100)),
+			)
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let started = std::time::Instant::now();
+		let infos = mock_client(&server)
+			.blogs_info(&["engineering", "deleted", "staff"], 2)
+			.await;
+
+		// three requests, two at a time
+		assert!(started.elapsed() >= std::time::Duration::from_millis(200));
+
+		assert_eq!(infos.len(), 3);
+		assert_eq!(infos[0].as_ref().unwrap().info.name, "engineering");
+		assert!(matches!(infos[1], Err(Error::Tumblr(_))));
+		assert_eq!(infos[2].as_ref().unwrap().info.name, "staff");
+	}
+
+	#[test]
+	fn test_content_rating() {
+		let BlogInfoResponse::<AuthedBlogInfo> { blog } = serde_json::from_str(
+			r#"{
+	"blog": {
+		"title": "Spicy",
+		"posts": 12,
+		"name": "spicy",
+		"updated": 1654041600,
+		"description": "",
+		"ask": false,
+		"is_nsfw": true,
+		"is_adult": false,
+		"is_blocked_from_primary": false
+	}
+}"#,
+		)
+		.unwrap();
+
+		assert_eq!(blog.info.is_nsfw, Some(true));
+		assert_eq!(blog.info.is_adult, Some(false));
+		assert_eq!(blog.is_blocked_from_primary, Some(false));
+
+		let BlogInfoResponse::<AuthedBlogInfo> { blog } = serde_json::from_str(STAFF_INFO).unwrap();
+		assert_eq!(blog.info.is_nsfw, None);
+	}
+
+	#[test]
+	fn test_follow_state() {
+		let BlogInfoResponse::<AuthedBlogInfo> { blog } = serde_json::from_str(
+			&STAFF_INFO.replace(
+				r#""ask": true,"#,
+				r#""ask": true, "followed": true, "followers": 33004929,"#,
+			),
+		)
+		.unwrap();
+
+		assert_eq!(blog.followed, Some(true));
+		assert_eq!(blog.followers, Some(33004929));
+
+		let BlogInfoResponse::<AuthedBlogInfo> { blog } = serde_json::from_str(STAFF_INFO).unwrap();
+		assert_eq!(blog.followed, None);
+		assert_eq!(blog.followers, None);
+	}
+
+	#[test]
+	fn test_options_default() {
+		fn query(options: &impl Serialize) -> String {
+			with_query("posts".to_owned(), options).unwrap()
+		}
+
+		assert_eq!(query(&GetPostsOptions::default()), "posts");
+		assert_eq!(
+			query(&GetPostsOptions {
+				limit: Some(5),
+				..Default::default()
+			}),
+			"posts?limit=5"
+		);
+		assert_eq!(
+			query(&SearchOptions {
+				offset: Some(20),
+				..Default::default()
+			}),
+			"posts?offset=20"
+		);
+		assert_eq!(
+			query(&QueueOptions {
+				limit: Some(5),
+				..Default::default()
+			}),
+			"posts?limit=5"
+		);
+		assert_eq!(
+			query(&LikesOptions {
+				before: Some(1654041600),
+				..Default::default()
+			}),
+			"posts?before=1654041600"
+		);
+		assert_eq!(
+			query(&TaggedOptions {
+				limit: Some(5),
+				..Default::default()
+			}),
+			"posts?limit=5"
+		);
+		assert_eq!(
+			query(&FetchPostOptions {
+				trail_mode: Some(TrailMode::Compact),
+				reblog_info: Some(true),
+			}),
+			"posts?trail_mode=compact&reblog_info=true"
+		);
+		assert_eq!(query(&NotesOptions::default()), "posts");
+		for (mode, expected) in [
+			(NotesMode::All, "all"),
+			(NotesMode::Likes, "likes"),
+			(NotesMode::Conversation, "conversation"),
+			(NotesMode::Rollup, "rollup"),
+			(NotesMode::ReblogsWithTags, "reblogs_with_tags"),
+		] {
+			assert_eq!(
+				query(&NotesOptions {
+					mode: Some(mode),
+					..Default::default()
+				}),
+				format!("posts?mode={expected}")
+			);
+		}
+		assert_eq!(
+			with_query(
+				"tagged?tag=cats".to_owned(),
+				&TaggedOptions {
+					limit: Some(5),
+					..Default::default()
+				}
+			)
+			.unwrap(),
+			"tagged?tag=cats&limit=5"
+		);
+	}
+
+	#[test]
+	fn test_options_query_keys() {
+		fn keys(options: &impl Serialize) -> Vec<String> {
+			serde_urlencoded::to_string(options)
+				.unwrap()
+				.split('&')
+				.map(|pair| pair.split_once('=').unwrap().0.to_owned())
+				.collect()
+		}
+
+		assert_eq!(
+			keys(&GetPostsOptions {
+				tag: Some(Tag::parse("corgi")),
+				limit: Some(5),
+				offset: Some(20),
+				before: Some(1654041600),
+				reblog_info: Some(true),
+				notes_info: Some(true),
+				trail_mode: Some(TrailMode::Full),
+				sort: Some(PostSort::Oldest),
+			}),
+			[
+				"tag",
+				"limit",
+				"offset",
+				"before",
+				"reblog_info",
+				"notes_info",
+				"trail_mode",
+				"sort"
+			]
+		);
+		assert_eq!(
+			keys(&SearchOptions {
+				limit: Some(5),
+				offset: Some(20),
+			}),
+			["limit", "offset"]
+		);
+		assert_eq!(
+			keys(&QueueOptions {
+				limit: Some(5),
+				offset: Some(20),
+			}),
+			["limit", "offset"]
+		);
+		assert_eq!(
+			keys(&LikesOptions {
+				limit: Some(5),
+				offset: Some(20),
+				before: Some(1654041600),
+				after: Some(1654041500),
+			}),
+			["limit", "offset", "before", "after"]
+		);
+		assert_eq!(
+			keys(&TaggedOptions {
+				before: Some(1654041600),
+				limit: Some(5),
+			}),
+			["before", "limit"]
+		);
+		assert_eq!(
+			keys(&FetchPostOptions {
+				trail_mode: Some(TrailMode::Compact),
+				reblog_info: Some(true),
+			}),
+			["trail_mode", "reblog_info"]
+		);
+		assert_eq!(
+			keys(&NotesOptions {
+				mode: Some(NotesMode::Rollup),
+				before_timestamp: Some(1654041600),
+			}),
+			["mode", "before_timestamp"]
+		);
+	}
+
+	#[test]
+	fn test_validate_limit() {
+		let options = |limit| GetPostsOptions {
+			limit: Some(limit),
+			offset: Some(40),
+			..Default::default()
+		};
+
+		assert!(matches!(options(0).validate(), Err(Error::Validation(_))));
+		assert!(matches!(options(21).validate(), Err(Error::Validation(_))));
+		assert!(options(20).validate().is_ok());
+		assert!(options(1).validate().is_ok());
+		assert!(GetPostsOptions::default().validate().is_ok());
+
+		assert!(matches!(
+			SearchOptions {
+				limit: Some(21),
+				offset: None,
+			}
+			.validate(),
+			Err(Error::Validation(_))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_info_fields() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.and(query_param("fields[blogs]", "name,posts"))
+			.respond_with(ok_response(r#"{"blog":{"name":"staff","posts":6500}}"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let info = mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.info_fields(&["name", "posts"])
+			.await
+			.unwrap();
+
+		assert_eq!(info, serde_json::json!({ "name": "staff", "posts": 6500 }));
+	}
+
+	#[tokio::test]
+	async fn test_unschedule() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("POST"))
+			.and(path("/blog/staff/post/edit"))
+			.and(header("content-type", "application/x-www-form-urlencoded"))
+			.and(body_string("id=1234&state=draft"))
+			.respond_with(ok_response(r#"{"id":"1234"}"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.unschedule(1234)
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_delete_queued_post() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("POST"))
+			.and(path("/blog/staff/post/delete"))
+			.and(body_string("id=1234"))
+			.respond_with(ok_response(r#"{"id":"1234"}"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.delete_post(1234)
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_post_count() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.respond_with(ok_response(STAFF_INFO))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let count = mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.post_count()
+			.await
+			.unwrap();
+
+		assert_eq!(count, 6500);
+	}
+
+	#[tokio::test]
+	async fn test_get_posts_sort() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts"))
+			.and(query_param("sort", "oldest"))
+			.respond_with(ok_response(
+				r#"{
+	"posts": [
+		{ "id": 1, "blog_name": "staff", "timestamp": 1654041500 },
+		{ "id": 2, "blog_name": "staff", "timestamp": 1654041600 }
+	],
+	"total_posts": 2
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let page = mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.get_posts(&GetPostsOptions {
+				sort: Some(PostSort::Oldest),
+				..Default::default()
+			})
+			.await
+			.unwrap();
+
+		assert_eq!(page.iter().map(|post| post.id).collect::<Vec<_>>(), [1, 2]);
+	}
+
+	#[tokio::test]
+	async fn test_get_posts_reblog_info() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts"))
+			.and(query_param("reblog_info", "true"))
+			.and(query_param("notes_info", "true"))
+			.respond_with(ok_response(
+				r#"{
+	"posts": [
+		{
+			"id": 2,
+			"blog_name": "staff",
+			"timestamp": 1654041600,
+			"reblogged_from_id": "1",
+			"reblogged_from_name": "parent",
+			"reblogged_root_name": "root",
+			"notes": [
+				{ "type": "like", "timestamp": 1654041700, "blog_name": "fan" },
+				{ "type": "posted", "timestamp": 1654041600, "blog_name": "staff" }
+			]
+		}
+	],
+	"total_posts": 1
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let page = mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.get_posts(&GetPostsOptions {
+				reblog_info: Some(true),
+				notes_info: Some(true),
+				..Default::default()
+			})
+			.await
+			.unwrap();
+
+		let post = &page.posts[0];
+		assert_eq!(post.reblog_info.reblogged_from_id, Some(1));
+		assert_eq!(
+			post.reblog_info.reblogged_from_name.as_deref(),
+			Some("parent")
+		);
+		assert_eq!(post.reblog_info.reblogged_root_name.as_deref(), Some("root"));
+		assert_eq!(
+			post.notes
+				.iter()
+				.flatten()
+				.map(|note| &note.note_type)
+				.collect::<Vec<_>>(),
+			vec![&NoteType::Like, &NoteType::Posted]
+		);
+	}
+
+	/// Three posts over two pages, where the page boundary falls between the two posts at `1654041500`.
+	/// Only the first page is served without `before`, so a resumed export can't fetch it
+	async fn mount_export_pages(server: &MockServer) {
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts"))
+			.and(query_param_is_missing("before"))
+			.respond_with(ok_response(
+				r#"{
+	"posts": [
+		{
+			"id": 3,
+			"blog_name": "staff",
+			"timestamp": 1654041600,
+			"tags": ["corgi"],
+			"content": [{ "type": "text", "text": "reblogged" }],
+			"trail": [
+				{
+					"post": { "id": 1 },
+					"blog": { "uuid": "t:parent", "name": "parent" },
+					"content": [{ "type": "text", "text": "original" }],
+					"layout": []
+				}
+			]
+		},
+		{
+			"id": 2,
+			"blog_name": "staff",
+			"timestamp": 1654041500,
+			"content": [{ "type": "text", "text": "second" }]
+		}
+	],
+	"total_posts": 3
+}"#,
+			))
+			.mount(server)
+			.await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts/2"))
+			.respond_with(ok_response(
+				r#"{ "id": 2, "blog_name": "staff", "timestamp": 1654041500 }"#,
+			))
+			.mount(server)
+			.await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts/4"))
+			.respond_with(
+				ResponseTemplate::new(404)
+					.set_body_string(r#"{"meta":{"status":404,"msg":"Not Found"},"response":[]}"#),
+			)
+			.mount(server)
+			.await;
+
+		// where a resumed export starts, at the timestamp of post 2
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts"))
+			.and(query_param("before", "1654041501"))
+			.and(query_param_is_missing("offset"))
+			.respond_with(ok_response(
+				r#"{
+	"posts": [
+		{ "id": 2, "blog_name": "staff", "timestamp": 1654041500 },
+		{
+			"id": 1,
+			"blog_name": "staff",
+			"timestamp": 1654041500,
+			"content": [{ "type": "text", "text": "first" }]
+		}
+	],
+	"total_posts": 3
+}"#,
+			))
+			.mount(server)
+			.await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts"))
+			.and(query_param("before", "1654041501"))
+			.and(query_param("offset", "1"))
+			.respond_with(ok_response(
+				r#"{
+	"posts": [
+		{
+			"id": 1,
+			"blog_name": "staff",
+			"timestamp": 1654041500,
+			"content": [{ "type": "text", "text": "first" }]
+		}
+	],
+	"total_posts": 3
+}"#,
+			))
+			.mount(server)
+			.await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts"))
+			.and(query_param("before", "1654041501"))
+			.and(query_param("offset", "2"))
+			.respond_with(ok_response(r#"{ "posts": [], "total_posts": 3 }"#))
+			.mount(server)
+			.await;
+	}
+
+	fn ndjson_lines(buffer: Vec<u8>) -> Vec<serde_json::Value> {
+		String::from_utf8(buffer)
+			.unwrap()
+			.lines()
+			.map(|line| serde_json::from_str(line).unwrap())
+			.collect()
+	}
+
+	#[tokio::test]
+	async fn test_export_ndjson() {
+		let server = MockServer::start().await;
+		mount_export_pages(&server).await;
+
+		let mut buffer = Vec::new();
+		let exported = mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.export_ndjson(&mut buffer, None)
+			.await
+			.unwrap();
+
+		assert_eq!(exported, 3);
+
+		let lines = ndjson_lines(buffer);
+
+		assert_eq!(lines.len(), 3);
+		assert_eq!(lines[0]["id"], 3);
+		assert_eq!(lines[0]["tags"], serde_json::json!(["corgi"]));
+		assert_eq!(lines[0]["trail"][0]["content"][0]["text"], "original");
+		assert_eq!(lines[1]["timestamp"], 1654041500);
+		// on the next page, but at the same timestamp as the last post of the first one
+		assert_eq!(lines[2]["timestamp"], 1654041500);
+		assert_eq!(lines[2]["content"][0]["text"], "first");
+	}
+
+	#[tokio::test]
+	async fn test_export_ndjson_resume() {
+		let server = MockServer::start().await;
+		mount_export_pages(&server).await;
+
+		let blog = mock_client(&server).blog("staff").unwrap();
+
+		let mut buffer = Vec::new();
+		let exported = blog.export_ndjson(&mut buffer, Some(2)).await.unwrap();
+
+		assert_eq!(exported, 1);
+		assert_eq!(
+			ndjson_lines(buffer)
+				.iter()
+				.map(|line| line["id"].clone())
+				.collect::<Vec<_>>(),
+			[1]
+		);
+
+		// the posts already exported aren't fetched again
+		for request in server.received_requests().await.unwrap() {
+			assert!(
+				request.url.path() == "/blog/staff/posts/2"
+					|| request.url.query_pairs().any(|(k, _)| k == "before"),
+				"refetched {}",
+				request.url
+			);
+		}
+
+		assert!(matches!(
+			blog.export_ndjson(Vec::new(), Some(4)).await,
+			Err(Error::Validation(_))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_export_ndjson_skips_shifted_posts() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts"))
+			.and(query_param_is_missing("before"))
+			.respond_with(ok_response(
+				r#"{
+	"posts": [
+		{ "id": 3, "blog_name": "staff", "timestamp": 1654041600 },
+		{ "id": 2, "blog_name": "staff", "timestamp": 1654041500 }
+	]
+}"#,
+			))
+			.mount(&server)
+			.await;
+
+		// overlaps the first page, as when a post is deleted during the export
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts"))
+			.and(query_param("before", "1654041501"))
+			.and(query_param("offset", "1"))
+			.respond_with(ok_response(
+				r#"{
+	"posts": [
+		{ "id": 2, "blog_name": "staff", "timestamp": 1654041500 },
+		{ "id": 1, "blog_name": "staff", "timestamp": 1654041400 }
+	]
+}"#,
+			))
+			.mount(&server)
+			.await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts"))
+			.and(query_param("before", "1654041401"))
+			.and(query_param("offset", "1"))
+			.respond_with(ok_response(r#"{ "posts": [] }"#))
+			.mount(&server)
+			.await;
+
+		let mut buffer = Vec::new();
+		let exported = mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.export_ndjson(&mut buffer, None)
+			.await
+			.unwrap();
+
+		assert_eq!(exported, 3);
+		assert_eq!(
+			ndjson_lines(buffer)
+				.iter()
+				.map(|line| line["id"].clone())
+				.collect::<Vec<_>>(),
+			[3, 2, 1]
+		);
+	}
+
+	#[tokio::test]
+	async fn test_rollup_notes() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/notes"))
+			.and(query_param("id", "1234"))
+			.and(query_param("mode", "rollup"))
+			.respond_with(ok_response(
+				r#"{
+	"notes": [
+		{ "type": "reply", "timestamp": 1654041900, "blog_name": "fan", "reply_text": "so true" }
+	],
+	"rollup_notes": [
+		{ "type": "reblog", "timestamp": 1654041800, "blog_name": "reblogger", "post_id": "5678" },
+		{ "type": "like", "timestamp": 1654041700, "blog_name": "liker" }
+	],
+	"total_notes": 42,
+	"total_likes": 30,
+	"total_reblogs": 11
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let page = mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.notes(
+				1234,
+				&NotesOptions {
+					mode: Some(NotesMode::Rollup),
+					..Default::default()
+				},
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(page.total_notes, Some(42));
+		assert_eq!(page.total_likes, Some(30));
+		assert_eq!(page.total_reblogs, Some(11));
+		assert_eq!(page.notes.len(), 1);
+		assert_eq!(page.notes[0].reply_text.as_deref(), Some("so true"));
+		assert_eq!(
+			page.rollup_notes
+				.iter()
+				.flatten()
+				.map(|note| (&note.note_type, note.blog_name.as_str()))
+				.collect::<Vec<_>>(),
+			vec![
+				(&NoteType::Reblog, "reblogger"),
+				(&NoteType::Like, "liker")
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn test_search_encodes_query() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/search/cats%20%26%20dogs%2F%23"))
+			.and(query_param("limit", "5"))
+			.respond_with(ok_response(r#"{"posts":[],"total_posts":0}"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/search/Corgi%20Pics"))
+			.respond_with(ok_response(r#"{"posts":[],"total_posts":0}"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let blog = mock_client(&server).blog("staff").unwrap();
+
+		let page = blog
+			.search(
+				"cats & dogs/#",
+				&SearchOptions {
+					limit: Some(5),
+					..Default::default()
+				},
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(page.posts, vec![]);
+
+		// the query keeps its case and the `%XX` escapes are signed as sent
+		blog.search("Corgi Pics", &SearchOptions::default())
+			.await
+			.unwrap();
+
+		for request in server.received_requests().await.unwrap() {
+			assert_valid_signature(&server, &request);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_likes() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/likes"))
+			.and(query_param("limit", "2"))
+			.respond_with(ok_response(
+				r#"{
+	"liked_posts": [
+		{ "id": 1, "blog_name": "someone", "timestamp": 1654041600, "liked": true },
+		{ "id": 2, "blog_name": "someone-else", "timestamp": 1654041500 }
+	],
+	"liked_count": 30
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let likes = mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.likes(&LikesOptions {
+				limit: Some(2),
+				..Default::default()
+			})
+			.await
+			.unwrap();
+
+		assert_eq!(likes.liked_count, Some(30));
+		assert_eq!(
+			likes
+				.liked_posts
+				.iter()
+				.map(|post| (post.id, post.liked))
+				.collect::<Vec<_>>(),
+			vec![(1, Some(true)), (2, None)]
+		);
+	}
+
+	#[tokio::test]
+	async fn test_likes_not_shared() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/likes"))
+			.respond_with(ResponseTemplate::new(403).set_body_raw(
+				r#"{"meta":{"status":403,"msg":"Forbidden"},"response":[]}"#,
+				"application/json",
+			))
+			.mount(&server)
+			.await;
+
+		let err = mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.likes(&LikesOptions::default())
+			.await
+			.unwrap_err();
+
+		assert!(matches!(err, Error::LikesNotShared(blog) if blog == "staff"));
+	}
+
+	#[tokio::test]
+	async fn test_search_disabled() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/search/cats"))
+			.respond_with(ResponseTemplate::new(403).set_body_raw(
+				r#"{"meta":{"status":403,"msg":"Forbidden"},"response":[]}"#,
+				"application/json",
+			))
+			.mount(&server)
+			.await;
+
+		let err = mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.search("cats", &SearchOptions::default())
+			.await
+			.unwrap_err();
+
+		assert!(matches!(err, Error::SearchDisabled(blog) if blog == "staff"));
+	}
+
+	#[tokio::test]
+	async fn test_set_avatar() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("POST"))
+			.and(path("/blog/staff/avatar"))
+			.respond_with(ok_response("{}"))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let blog = mock_client(&server).blog("staff").unwrap();
+
+		let image = |mime_type: &str, data: Vec<u8>| {
+			MediaFile::new("avatar".to_owned(), mime_type.to_owned(), data).unwrap()
+		};
+
+		blog.set_avatar(image("image/png", b"not really a png".to_vec()))
+			.await
+			.unwrap();
+
+		for invalid in [
+			image("video/mp4", b"not really an mp4".to_vec()),
+			image("image/png", Vec::new()),
+			image("image/png", vec![0; MAX_AVATAR_SIZE + 1]),
+		] {
+			assert!(matches!(
+				blog.set_avatar(invalid).await,
+				Err(Error::Validation(_))
+			));
+		}
+
+		let request = &server.received_requests().await.unwrap()[0];
+
+		let boundary = request
+			.headers
+			.iter()
+			.find(|(name, _)| name.as_str() == "content-type")
+			.map(|(_, values)| values.as_str())
+			.unwrap()
+			.strip_prefix("multipart/form-data; boundary=")
+			.unwrap()
+			.to_owned();
+
+		assert_eq!(
+			String::from_utf8(request.body.clone()).unwrap(),
+			format!(
+				"--{boundary}\r\n\
+				Content-Disposition: form-data; name=\"data\"; filename=\"data\"\r\n\
+				Content-Type: image/png\r\n\r\n\
+				not really a png\r\n\
+				--{boundary}--\r\n"
+			)
+		);
+	}
+
+	#[tokio::test]
+	async fn test_update_theme() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("POST"))
+			.and(path("/blog/staff/theme"))
+			.and(body_json(serde_json::json!({
+				"avatar_shape": "circle",
+				"background_color": "#001935",
+				"show_title": false,
+			})))
+			.respond_with(ok_response("{}"))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		mock_client(&server)
+			.blog("staff")
+			.unwrap()
+			.update_theme(&BlogTheme {
+				avatar_shape: Some(AvatarShape::Circle),
+				background_color: Some("#001935".to_owned()),
+				show_title: Some(false),
+				..Default::default()
+			})
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_posts_stream() {
+		let server = MockServer::start().await;
+
+		let post = |id: u64| {
+			serde_json::json!({ "id": id, "blog_name": "staff", "timestamp": 1654041600 - id })
+		};
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts"))
+			.and(query_param("tag", "corgi"))
+			.and(query_param_is_missing("offset"))
+			.respond_with(ok_response(
+				&serde_json::json!({
+					"posts": [post(1), post(2)],
+					"_links": {
+						"next": {
+							"href": "/v2/blog/staff/posts?limit=2&offset=2&tag=corgi",
+							"method": "GET",
+							"query_params": { "limit": "2", "offset": "2", "tag": "corgi" }
+						}
+					}
+				})
+				.to_string(),
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		// fewer posts than the limit, so the stream ends even though there's a `next` link
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts"))
+			.and(query_param("tag", "corgi"))
+			.and(query_param("offset", "2"))
+			.respond_with(ok_response(
+				&serde_json::json!({
+					"posts": [post(3)],
+					"_links": {
+						"next": {
+							"href": "/v2/blog/staff/posts?limit=2&offset=3&tag=corgi",
+							"method": "GET",
+							"query_params": { "limit": "2", "offset": "3", "tag": "corgi" }
+						}
+					}
+				})
+				.to_string(),
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let blog = mock_client(&server).blog("staff").unwrap();
+
+		let ids: Vec<_> = blog
+			.posts_stream(GetPostsOptions {
+				tag: Some(Tag::parse("corgi")),
+				limit: Some(2),
+				..Default::default()
+			})
+			.map(|post| post.unwrap().id)
+			.collect()
+			.await;
+
+		assert_eq!(ids, [1, 2, 3]);
+
+		let invalid: Vec<_> = blog
+			.posts_stream(GetPostsOptions {
+				limit: Some(MAX_LIMIT + 1),
+				..Default::default()
+			})
+			.collect()
+			.await;
+
+		assert!(matches!(&invalid[..], [Err(Error::Validation(_))]));
 	}
 }
@@ -0,0 +1,112 @@
+use std::{
+	collections::{HashMap, VecDeque},
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+/// A small least-recently-used cache whose entries expire after `ttl`
+#[derive(Debug)]
+pub(crate) struct Cache<V: Clone> {
+	capacity: usize,
+	ttl: Duration,
+	inner: Mutex<CacheInner<V>>,
+}
+
+#[derive(Debug)]
+struct CacheInner<V> {
+	entries: HashMap<String, (Instant, V)>,
+	/// least recently used first
+	order: VecDeque<String>,
+}
+
+impl<V: Clone> CacheInner<V> {
+	fn remove(&mut self, key: &str) {
+		self.entries.remove(key);
+		self.order.retain(|k| k != key);
+	}
+}
+
+impl<V: Clone> Cache<V> {
+	pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+		Self {
+			capacity,
+			ttl,
+			inner: Mutex::new(CacheInner {
+				entries: HashMap::with_capacity(capacity),
+				order: VecDeque::with_capacity(capacity),
+			}),
+		}
+	}
+
+	/// A new, empty cache with the same capacity and ttl
+	pub(crate) fn empty_like(&self) -> Self {
+		Self::new(self.capacity, self.ttl)
+	}
+
+	pub(crate) fn get(&self, key: &str) -> Option<V> {
+		let mut inner = self.inner.lock().expect("cache poisoned");
+
+		let (inserted, value) = inner.entries.get(key)?;
+
+		if inserted.elapsed() > self.ttl {
+			inner.remove(key);
+			return None;
+		}
+
+		let value = value.clone();
+		inner.order.retain(|k| k != key);
+		inner.order.push_back(key.to_owned());
+
+		Some(value)
+	}
+
+	pub(crate) fn insert(&self, key: String, value: V) {
+		if self.capacity == 0 {
+			return;
+		}
+
+		let mut inner = self.inner.lock().expect("cache poisoned");
+
+		inner.remove(&key);
+
+		while inner.entries.len() >= self.capacity {
+			match inner.order.pop_front() {
+				Some(oldest) => {
+					inner.entries.remove(&oldest);
+				}
+				None => break,
+			}
+		}
+
+		inner.order.push_back(key.clone());
+		inner.entries.insert(key, (Instant::now(), value));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_evicts_least_recently_used() {
+		let cache = Cache::new(2, Duration::from_secs(60));
+
+		cache.insert("a".to_owned(), 1);
+		cache.insert("b".to_owned(), 2);
+		assert_eq!(cache.get("a"), Some(1));
+
+		cache.insert("c".to_owned(), 3);
+		assert_eq!(cache.get("b"), None);
+		assert_eq!(cache.get("a"), Some(1));
+		assert_eq!(cache.get("c"), Some(3));
+	}
+
+	#[test]
+	fn test_expires_after_ttl() {
+		let cache = Cache::new(2, Duration::ZERO);
+
+		cache.insert("a".to_owned(), 1);
+		std::thread::sleep(Duration::from_millis(1));
+		assert_eq!(cache.get("a"), None);
+	}
+}
@@ -1,15 +1,34 @@
-use std::sync::Arc;
+use std::{fmt, ops::Deref, sync::Arc, time::Duration};
 
-use crate::oauth::OAuthCredentials;
+use crate::{
+	blog::AuthedBlogInfo,
+	cache::Cache,
+	coalesce::Coalescer,
+	etag::ETags,
+	metrics::{Metrics, NoopMetrics},
+	oauth::OAuthCredentials,
+	retry::RetryPolicy,
+	user::UserBlog,
+};
 
 mod sealed {
 	pub trait ClientStateSealed {}
 }
 
 pub trait State: sealed::ClientStateSealed {
-	type Keys: std::ops::Deref<Target = [(&'static str, str)]>;
+	/// which state this is, for code generic over `S: State` that needs to know at runtime
+	const AUTH_STATE: AuthStateKind;
+}
 
-	fn keys(&self) -> Self::Keys;
+/// The [`State`] a [`Client`] is in, see [`Client::auth_state`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuthStateKind {
+	/// only the consumer key, see [`Unauthenticated`]
+	Unauthenticated,
+	/// partway through the OAuth flow, see [`Temporary`]
+	Temporary,
+	/// signing requests as a user, see [`Authenticated`]
+	Authenticated,
 }
 
 #[derive(Debug)]
@@ -21,19 +40,78 @@ pub struct Authenticated(pub(crate) OAuthCredentials);
 #[derive(Debug)]
 pub struct Temporary(pub(crate) OAuthCredentials);
 
-#[derive(Debug)]
+impl sealed::ClientStateSealed for Unauthenticated {}
+impl State for Unauthenticated {
+	const AUTH_STATE: AuthStateKind = AuthStateKind::Unauthenticated;
+}
+
+impl sealed::ClientStateSealed for Authenticated {}
+impl State for Authenticated {
+	const AUTH_STATE: AuthStateKind = AuthStateKind::Authenticated;
+}
+
+impl sealed::ClientStateSealed for Temporary {}
+impl State for Temporary {
+	const AUTH_STATE: AuthStateKind = AuthStateKind::Temporary;
+}
+
 pub struct ClientInner<S: State> {
-	client: reqwest::Client,
-	oauth_consumer_key: String,
-	oauth_client_secret: String,
-	state: S,
+	pub(crate) client: reqwest::Client,
+	pub(crate) api_base: String,
+	pub(crate) oauth_base: String,
+	pub(crate) oauth_consumer_key: String,
+	pub(crate) oauth_client_secret: String,
+	pub(crate) blog_info_cache: Option<Cache<AuthedBlogInfo>>,
+	pub(crate) primary_blog_cache: Option<Cache<UserBlog>>,
+	pub(crate) coalescer: Option<Coalescer>,
+	pub(crate) etags: Option<ETags>,
+	pub(crate) metrics: Arc<dyn Metrics>,
+	pub(crate) retry: RetryPolicy,
+	pub(crate) state: S,
+}
+
+impl<S: State + fmt::Debug> fmt::Debug for ClientInner<S> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ClientInner")
+			.field("client", &self.client)
+			.field("api_base", &self.api_base)
+			.field("oauth_base", &self.oauth_base)
+			.field("oauth_consumer_key", &self.oauth_consumer_key)
+			.field("oauth_client_secret", &"***")
+			.field("blog_info_cache", &self.blog_info_cache)
+			.field("primary_blog_cache", &self.primary_blog_cache)
+			.field("coalescer", &self.coalescer)
+			.field("etags", &self.etags)
+			.field("metrics", &self.metrics)
+			.field("retry", &self.retry)
+			.field("state", &self.state)
+			.finish()
+	}
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Client<S: State> {
 	inner: Arc<ClientInner<S>>,
 }
 
+impl<S: State> Clone for Client<S> {
+	#[inline]
+	fn clone(&self) -> Self {
+		Self {
+			inner: Arc::clone(&self.inner),
+		}
+	}
+}
+
+impl<S: State> Deref for Client<S> {
+	type Target = ClientInner<S>;
+
+	#[inline]
+	fn deref(&self) -> &Self::Target {
+		&self.inner
+	}
+}
+
 static USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), " v", env!("CARGO_PKG_VERSION"));
 
 impl<S: State> Client<S> {
@@ -42,9 +120,32 @@ impl<S: State> Client<S> {
 		&self.inner.state
 	}
 
+	/// The client's [`State`], as a value rather than a type
 	#[inline]
-	pub(crate) fn state_mut(&mut self) -> &mut S {
-		&mut self.inner.state
+	pub fn auth_state(&self) -> AuthStateKind {
+		S::AUTH_STATE
+	}
+
+	/// Waits for any coalesced requests still in flight (see [`ClientBuilder::coalesce_requests`]) so a program can exit cleanly.
+	/// The client persists nothing else, so otherwise this returns immediately
+	pub async fn shutdown(self) {
+		if let Some(coalescer) = &self.coalescer {
+			coalescer.drain().await;
+		}
+	}
+
+	/// Sends `req` as is, reporting it to the client's [`Metrics`]
+	pub(crate) async fn execute(&self, req: reqwest::Request) -> crate::Result<reqwest::Response> {
+		self.metrics.on_request(req.method(), req.url());
+
+		let res = self.client.execute(req).await?;
+
+		self.metrics.on_response(res.status().as_u16());
+		if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+			self.metrics.on_rate_limited();
+		}
+
+		Ok(res)
 	}
 
 	/// Attempts to wrap the client with the given state
@@ -53,38 +154,257 @@ impl<S: State> Client<S> {
 		match Arc::try_unwrap(self.inner) {
 			Ok(ClientInner {
 				client,
+				api_base,
+				oauth_base,
 				oauth_consumer_key,
 				oauth_client_secret,
+				blog_info_cache,
+				primary_blog_cache,
+				coalescer,
+				etags,
+				metrics,
+				retry,
 				..
 			}) => Ok(Client {
 				inner: Arc::new(ClientInner {
 					client,
+					api_base,
+					oauth_base,
 					oauth_consumer_key,
 					oauth_client_secret,
+					blog_info_cache,
+					primary_blog_cache,
+					coalescer,
+					etags,
+					metrics,
+					retry,
 					state,
 				}),
 			}),
 			Err(inner) => Err((Self { inner }, state)),
 		}
 	}
+
+	/// Wraps a copy of the client with the given state, sharing the underlying HTTP client and [`Metrics`].
+	/// Caches, coalesced requests, and `ETag`s start empty, since they are tied to the original client's state
+	pub(crate) fn clone_into_other_state<U: State>(&self, state: U) -> Client<U> {
+		Client {
+			inner: Arc::new(ClientInner {
+				client: self.client.clone(),
+				api_base: self.api_base.clone(),
+				oauth_base: self.oauth_base.clone(),
+				oauth_consumer_key: self.oauth_consumer_key.clone(),
+				oauth_client_secret: self.oauth_client_secret.clone(),
+				blog_info_cache: self.blog_info_cache.as_ref().map(Cache::empty_like),
+				primary_blog_cache: self.primary_blog_cache.as_ref().map(Cache::empty_like),
+				coalescer: self.coalescer.as_ref().map(|_| Coalescer::default()),
+				etags: self.etags.as_ref().map(|_| ETags::default()),
+				metrics: Arc::clone(&self.metrics),
+				retry: self.retry,
+				state,
+			}),
+		}
+	}
 }
 
-impl Client<Unauthenticated> {
-	/// Creates a new unauthenticated `Client` with the given OAuth application keys
-	pub fn new(oauth_consumer_key: String, oauth_client_secret: String) -> Self {
-		Self {
+/// Builder for a `Client<Unauthenticated>`, see [`Client::builder`]
+#[derive(Debug)]
+pub struct ClientBuilder {
+	oauth_consumer_key: String,
+	oauth_client_secret: String,
+	user_agent: Option<String>,
+	blog_info_cache: Option<(usize, Duration)>,
+	coalesce_requests: bool,
+	conditional_requests: bool,
+	metrics: Option<Arc<dyn Metrics>>,
+	timeout: Option<Duration>,
+	pool_max_idle_per_host: Option<usize>,
+	pool_idle_timeout: Option<Duration>,
+	retry: RetryPolicy,
+	#[cfg(test)]
+	api_base: Option<String>,
+	#[cfg(test)]
+	oauth_base: Option<String>,
+}
+
+impl ClientBuilder {
+	/// Identifies the application in the `User-Agent` header.
+	/// The crate name and version are still appended, e.g. `my-app/1.0 tumblr-api v0.1.0`
+	pub fn user_agent(mut self, user_agent: String) -> Self {
+		self.user_agent = Some(user_agent);
+		self
+	}
+
+	/// Caches up to `capacity` results of [`Blog::info`](crate::blog::Blog::info) for `ttl`,
+	/// as well as the result of [`Client::primary_blog`].
+	/// The cache is shared by every `Blog` (and clone) of the built client.  Disabled by default
+	pub fn blog_info_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+		self.blog_info_cache = Some((capacity, ttl));
+		self
+	}
+
+	/// Makes concurrent identical `GET`s share a single request and response.  Disabled by default
+	pub fn coalesce_requests(mut self, coalesce_requests: bool) -> Self {
+		self.coalesce_requests = coalesce_requests;
+		self
+	}
+
+	/// Remembers the `ETag` of each `GET` response and sends it back as `If-None-Match`,
+	/// so unchanged resources return [`Error::NotModified`](crate::Error::NotModified) instead of the whole body.
+	/// Not every endpoint sends an `ETag`, so this is disabled by default
+	pub fn conditional_requests(mut self, conditional_requests: bool) -> Self {
+		self.conditional_requests = conditional_requests;
+		self
+	}
+
+	/// Reports every request the client makes to `metrics`.  Defaults to [`NoopMetrics`]
+	pub fn metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+		self.metrics = Some(metrics);
+		self
+	}
+
+	/// Fails requests that take longer than `timeout` with [`Error::Timeout`](crate::Error::Timeout).
+	/// Can be overridden per request with [`Client::request_with_timeout`].  No timeout by default
+	pub fn timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = Some(timeout);
+		self
+	}
+
+	/// Keeps at most `max` idle connections open to each host.  Unlimited by default.
+	/// Every API call goes to the same host, so set this at least as high as the number of concurrent calls,
+	/// or connections will be closed and reopened between bursts
+	pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+		self.pool_max_idle_per_host = Some(max);
+		self
+	}
+
+	/// Closes connections that have been idle for `timeout`.
+	/// Defaults to 90 seconds, which keeps connections warm between calls a few seconds apart without holding them overnight
+	pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+		self.pool_idle_timeout = Some(timeout);
+		self
+	}
+
+	/// Retries an API call answered with `429 Too Many Requests` up to `max_retries` times, signing it afresh each time.
+	/// Waits as long as the response's `Retry-After` header asks, or else `base_backoff`, doubled after each retry.
+	/// If that's longer than `max_delay`, e.g. once the daily quota is used up, returns the [`Error::Tumblr`](crate::Error::Tumblr) right away instead.
+	/// Off by default, so rate limits surface right away as [`Error::Tumblr`](crate::Error::Tumblr)
+	pub fn retry_rate_limited(
+		mut self,
+		max_retries: u32,
+		base_backoff: Duration,
+		max_delay: Duration,
+	) -> Self {
+		self.retry = RetryPolicy {
+			max_retries,
+			base_backoff,
+			max_delay,
+		};
+		self
+	}
+
+	/// Points the client at a plain HTTP mock server
+	#[cfg(test)]
+	pub(crate) fn api_base(mut self, api_base: String) -> Self {
+		self.api_base = Some(api_base);
+		self
+	}
+
+	/// Points the OAuth token endpoints at a plain HTTP mock server
+	#[cfg(test)]
+	pub(crate) fn oauth_base(mut self, oauth_base: String) -> Self {
+		self.oauth_base = Some(oauth_base);
+		self
+	}
+
+	pub fn build(self) -> Client<Unauthenticated> {
+		let user_agent = match self.user_agent {
+			Some(user_agent) => format!("{user_agent} {USER_AGENT}"),
+			None => USER_AGENT.to_owned(),
+		};
+
+		#[cfg(test)]
+		let (api_base, https_only) = match self.api_base {
+			Some(api_base) => (api_base, false),
+			None => (Client::API_BASE.to_owned(), true),
+		};
+		#[cfg(not(test))]
+		let (api_base, https_only) = (Client::API_BASE.to_owned(), true);
+
+		#[cfg(test)]
+		let oauth_base = self
+			.oauth_base
+			.unwrap_or_else(|| Client::OAUTH_BASE.to_owned());
+		#[cfg(not(test))]
+		let oauth_base = Client::OAUTH_BASE.to_owned();
+
+		let mut client = reqwest::Client::builder()
+			.user_agent(user_agent)
+			.https_only(https_only);
+
+		if let Some(timeout) = self.timeout {
+			client = client.timeout(timeout);
+		}
+
+		if let Some(max) = self.pool_max_idle_per_host {
+			client = client.pool_max_idle_per_host(max);
+		}
+
+		if let Some(timeout) = self.pool_idle_timeout {
+			client = client.pool_idle_timeout(timeout);
+		}
+
+		Client {
 			inner: Arc::new(ClientInner {
-				client: reqwest::Client::builder()
-					.user_agent(USER_AGENT)
-					.https_only(true)
-					.build()
-					.expect("tumblr-api::Client::new"),
-				oauth_consumer_key,
-				oauth_client_secret,
+				client: client.build().expect("tumblr-api::Client::new"),
+				api_base,
+				oauth_base,
+				oauth_consumer_key: self.oauth_consumer_key,
+				oauth_client_secret: self.oauth_client_secret,
+				blog_info_cache: self
+					.blog_info_cache
+					.map(|(capacity, ttl)| Cache::new(capacity, ttl)),
+				primary_blog_cache: self.blog_info_cache.map(|(_, ttl)| Cache::new(1, ttl)),
+				coalescer: self.coalesce_requests.then(Coalescer::default),
+				etags: self.conditional_requests.then(ETags::default),
+				metrics: self.metrics.unwrap_or_else(|| Arc::new(NoopMetrics)),
+				retry: self.retry,
 				state: Unauthenticated,
 			}),
 		}
 	}
+}
+
+impl Client<Unauthenticated> {
+	pub const API_BASE: &'static str = "https://api.tumblr.com/v2";
+	pub const OAUTH_BASE: &'static str = "https://www.tumblr.com/oauth";
+
+	/// Creates a new unauthenticated `Client` with the given OAuth application keys
+	#[inline]
+	pub fn new(oauth_consumer_key: String, oauth_client_secret: String) -> Self {
+		Self::builder(oauth_consumer_key, oauth_client_secret).build()
+	}
+
+	/// Creates a [`ClientBuilder`] with the given OAuth application keys
+	pub fn builder(oauth_consumer_key: String, oauth_client_secret: String) -> ClientBuilder {
+		ClientBuilder {
+			oauth_consumer_key,
+			oauth_client_secret,
+			user_agent: None,
+			blog_info_cache: None,
+			coalesce_requests: false,
+			conditional_requests: false,
+			metrics: None,
+			timeout: None,
+			pool_max_idle_per_host: None,
+			pool_idle_timeout: None,
+			retry: RetryPolicy::default(),
+			#[cfg(test)]
+			api_base: None,
+			#[cfg(test)]
+			oauth_base: None,
+		}
+	}
 
 	/// Attempts to wrap the provided credentials into an authenticated `Client`
 	/// Returns an `Err` containing the `Client<Unauthenticated>` and the provided `OAuthCredentials` if the `Client` is has more than one reference to it (i.e., [`Arc::try_unwrap`] fails)
@@ -96,4 +416,306 @@ impl Client<Unauthenticated> {
 		self.try_into_other_state(Authenticated(credentials))
 			.map_err(|(client, state)| (client, state.0))
 	}
+
+	/// Like [`Client::with_credentials`], but always succeeds.
+	/// If the `Client` is referenced somewhere else, the authenticated client is rebuilt from a copy of its settings instead,
+	/// which is cheap since the underlying HTTP client is itself reference counted
+	pub fn with_credentials_cloned(self, credentials: OAuthCredentials) -> Client<Authenticated> {
+		self.try_into_other_state(Authenticated(credentials))
+			.unwrap_or_else(|(client, state)| client.clone_into_other_state(state))
+	}
+}
+
+impl Client<Authenticated> {
+	/// An unauthenticated client sharing this one's HTTP client and application keys, but not the user's token,
+	/// e.g. for a single public call signed with only the `api_key`
+	#[inline]
+	pub fn as_unauthenticated(&self) -> Client<Unauthenticated> {
+		self.clone_into_other_state(Unauthenticated)
+	}
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use wiremock::{
+		matchers::{header, header_exists, method, path, query_param},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	use std::sync::{
+		atomic::{AtomicUsize, Ordering},
+		Mutex,
+	};
+
+	use super::*;
+
+	/// A builder pointed at the given mock server
+	pub(crate) fn mock_builder(server: &MockServer) -> ClientBuilder {
+		Client::builder("consumer-key".to_owned(), "client-secret".to_owned())
+			.api_base(server.uri())
+			.oauth_base(format!("{}/oauth", server.uri()))
+	}
+
+	/// Builds and authenticates the client with dummy credentials
+	pub(crate) fn authenticated(builder: ClientBuilder) -> Client<Authenticated> {
+		builder
+			.build()
+			.with_credentials(OAuthCredentials {
+				oauth_token: "token".to_owned(),
+				oauth_token_secret: "token-secret".to_owned(),
+			})
+			.unwrap()
+	}
+
+	/// An authenticated client pointed at the given mock server
+	pub(crate) fn mock_client(server: &MockServer) -> Client<Authenticated> {
+		authenticated(mock_builder(server))
+	}
+
+	/// A successful API response wrapping `response`
+	pub(crate) fn ok_response(response: &str) -> ResponseTemplate {
+		ResponseTemplate::new(200).set_body_raw(
+			format!(r#"{{"meta":{{"status":200,"msg":"OK"}},"response":{response}}}"#),
+			"application/json",
+		)
+	}
+
+	#[test]
+	fn test_debug_redacts_secrets() {
+		let client = Client::new(
+			"consumer-key".to_owned(),
+			"super-secret-client-secret".to_owned(),
+		);
+
+		let debug = format!("{:?}", client);
+		assert!(!debug.contains("super-secret-client-secret"));
+		assert!(debug.contains("consumer-key"));
+
+		let client = client
+			.with_credentials(OAuthCredentials {
+				oauth_token: "super-secret-token".to_owned(),
+				oauth_token_secret: "super-secret-token-secret".to_owned(),
+			})
+			.unwrap();
+
+		let debug = format!("{:?}", client);
+		assert!(!debug.contains("super-secret-client-secret"));
+		assert!(!debug.contains("super-secret-token"));
+		assert!(!debug.contains("super-secret-token-secret"));
+		assert!(debug.contains("***"));
+	}
+
+	#[derive(Debug, Default)]
+	struct RecordingMetrics {
+		requests: AtomicUsize,
+		responses: Mutex<Vec<u16>>,
+		rate_limited: AtomicUsize,
+	}
+
+	impl Metrics for RecordingMetrics {
+		fn on_request(&self, _method: &reqwest::Method, _url: &reqwest::Url) {
+			self.requests.fetch_add(1, Ordering::SeqCst);
+		}
+
+		fn on_response(&self, status: u16) {
+			self.responses.lock().unwrap().push(status);
+		}
+
+		fn on_rate_limited(&self) {
+			self.rate_limited.fetch_add(1, Ordering::SeqCst);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_metrics() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.respond_with(ok_response("{}"))
+			.mount(&server)
+			.await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/busy/info"))
+			.respond_with(ResponseTemplate::new(429).set_body_raw(
+				r#"{"meta":{"status":429,"msg":"Limit Exceeded"},"response":[]}"#,
+				"application/json",
+			))
+			.mount(&server)
+			.await;
+
+		let metrics = Arc::new(RecordingMetrics::default());
+		let client = authenticated(mock_builder(&server).metrics(metrics.clone()));
+
+		client.blog_info("staff").await.unwrap();
+		client.blog_info("busy").await.unwrap_err();
+
+		assert_eq!(metrics.requests.load(Ordering::SeqCst), 2);
+		assert_eq!(*metrics.responses.lock().unwrap(), vec![200, 429]);
+		assert_eq!(metrics.rate_limited.load(Ordering::SeqCst), 1);
+	}
+
+	#[tokio::test]
+	async fn test_shutdown_waits_for_in_flight() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.respond_with(ok_response("{}").set_delay(Duration::from_millis(200)))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let client = authenticated(mock_builder(&server).coalesce_requests(true));
+
+		let request = tokio::spawn({
+			let client = client.clone();
+			async move { client.blog_info("staff").await }
+		});
+
+		while client.coalescer.as_ref().unwrap().len() == 0 {
+			tokio::task::yield_now().await;
+		}
+
+		let started = std::time::Instant::now();
+		client.shutdown().await;
+
+		assert!(started.elapsed() >= Duration::from_millis(100));
+		assert!(request.await.unwrap().is_ok());
+	}
+
+	#[tokio::test]
+	async fn test_custom_user_agent() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.and(header(
+				"user-agent",
+				concat!(
+					"my-app/1.0 ",
+					env!("CARGO_PKG_NAME"),
+					" v",
+					env!("CARGO_PKG_VERSION")
+				),
+			))
+			.respond_with(ok_response("{}"))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let client = authenticated(mock_builder(&server).user_agent("my-app/1.0".to_owned()));
+
+		client.blog_info("staff").await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_pool_settings() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.respond_with(ok_response("{}"))
+			.expect(2)
+			.mount(&server)
+			.await;
+
+		let client = authenticated(
+			mock_builder(&server)
+				.pool_max_idle_per_host(4)
+				.pool_idle_timeout(Duration::from_secs(30)),
+		);
+
+		client.blog_info("staff").await.unwrap();
+		client.blog_info("staff").await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_with_credentials_shared() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.and(header_exists("authorization"))
+			.respond_with(ok_response("{}"))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let metrics = Arc::new(RecordingMetrics::default());
+		let client = mock_builder(&server).metrics(metrics.clone()).build();
+		let shared = client.clone();
+
+		let credentials = || OAuthCredentials {
+			oauth_token: "token".to_owned(),
+			oauth_token_secret: "token-secret".to_owned(),
+		};
+
+		let (client, _) = client.with_credentials(credentials()).unwrap_err();
+
+		let authenticated = client.with_credentials_cloned(credentials());
+		authenticated.blog_info("staff").await.unwrap();
+
+		assert_eq!(authenticated.state().0.oauth_token, "token");
+		assert_eq!(metrics.requests.load(Ordering::SeqCst), 1);
+		drop(shared);
+	}
+
+	#[tokio::test]
+	async fn test_as_unauthenticated() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.and(query_param("api_key", "consumer-key"))
+			.respond_with(ok_response(
+				r#"{"blog": {"title": "Tumblr Staff", "posts": 6500, "name": "staff", "updated": 1654041600, "description": "", "ask": true}}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let client = mock_client(&server);
+		let info = client
+			.as_unauthenticated()
+			.blog_info("staff")
+			.await
+			.unwrap();
+
+		assert_eq!(info.name, "staff");
+		assert_eq!(client.state().0.oauth_token, "token");
+
+		let request = &server.received_requests().await.unwrap()[0];
+		assert!(request
+			.headers
+			.iter()
+			.all(|(name, _)| name.as_str() != "authorization"));
+		assert!(!request.url.as_str().contains("token"));
+	}
+
+	#[test]
+	fn test_auth_state() {
+		fn generic<S: State>(client: &Client<S>) -> AuthStateKind {
+			client.auth_state()
+		}
+
+		let unauthenticated = Client::new("consumer-key".to_owned(), "client-secret".to_owned());
+		let temporary = unauthenticated.clone_into_other_state(Temporary(OAuthCredentials {
+			oauth_token: "temporary-token".to_owned(),
+			oauth_token_secret: "temporary-secret".to_owned(),
+		}));
+		let authenticated = authenticated(Client::builder(
+			"consumer-key".to_owned(),
+			"client-secret".to_owned(),
+		));
+
+		assert_eq!(generic(&unauthenticated), AuthStateKind::Unauthenticated);
+		assert_eq!(generic(&temporary), AuthStateKind::Temporary);
+		assert_eq!(generic(&authenticated), AuthStateKind::Authenticated);
+		assert_eq!(
+			authenticated.as_unauthenticated().auth_state(),
+			AuthStateKind::Unauthenticated
+		);
+	}
 }
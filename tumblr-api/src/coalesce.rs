@@ -0,0 +1,82 @@
+use std::{
+	collections::HashMap,
+	fmt,
+	future::Future,
+	sync::{Arc, Mutex},
+};
+
+use futures_util::future::{join_all, BoxFuture, FutureExt, Shared};
+
+use crate::Error;
+
+type SharedResponse = Shared<BoxFuture<'static, Result<Arc<String>, Arc<Error>>>>;
+
+/// Shares one in-flight request between every caller asking for the same key
+#[derive(Default)]
+pub(crate) struct Coalescer {
+	in_flight: Mutex<HashMap<String, SharedResponse>>,
+}
+
+impl fmt::Debug for Coalescer {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Coalescer")
+			.field(
+				"in_flight",
+				&self.in_flight.lock().map(|in_flight| in_flight.len()),
+			)
+			.finish()
+	}
+}
+
+impl Coalescer {
+	/// Awaits the request already in flight for `key`, or starts `request` if there isn't one
+	pub(crate) async fn run<F>(&self, key: String, request: F) -> crate::Result<Arc<String>>
+	where
+		F: Future<Output = crate::Result<String>> + Send + 'static,
+	{
+		let shared = self
+			.in_flight
+			.lock()
+			.expect("coalescer poisoned")
+			.entry(key.clone())
+			.or_insert_with(|| {
+				request
+					.map(|res| res.map(Arc::new).map_err(Arc::new))
+					.boxed()
+					.shared()
+			})
+			.clone();
+
+		let res = shared.clone().await;
+
+		let mut in_flight = self.in_flight.lock().expect("coalescer poisoned");
+		// a later request may already have replaced ours
+		if in_flight
+			.get(&key)
+			.is_some_and(|current| current.ptr_eq(&shared))
+		{
+			in_flight.remove(&key);
+		}
+
+		res.map_err(Error::Shared)
+	}
+
+	/// Waits for every request that was in flight when called
+	pub(crate) async fn drain(&self) {
+		let in_flight: Vec<_> = self
+			.in_flight
+			.lock()
+			.expect("coalescer poisoned")
+			.values()
+			.cloned()
+			.collect();
+
+		join_all(in_flight).await;
+	}
+
+	/// Number of distinct requests currently in flight
+	#[cfg(test)]
+	pub(crate) fn len(&self) -> usize {
+		self.in_flight.lock().expect("coalescer poisoned").len()
+	}
+}
@@ -0,0 +1,86 @@
+//! [`chrono`] conversions for the API's seconds-from-epoch timestamps.
+//! The fields themselves stay `u64`; use the accessors here or [`epoch_seconds`] in your own types
+
+use chrono::{DateTime, Utc};
+
+use crate::{blog::BlogInfo, post::FetchedPost};
+
+/// Converts seconds from epoch, saturating at chrono's supported range
+pub fn from_epoch_seconds(seconds: u64) -> DateTime<Utc> {
+	i64::try_from(seconds)
+		.ok()
+		.and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+		.unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+/// For `#[serde(with = "tumblr_api::datetime::epoch_seconds")]` on a `DateTime<Utc>` field
+pub mod epoch_seconds {
+	use chrono::{DateTime, Utc};
+	use serde::{Deserialize, Deserializer, Serializer};
+
+	pub fn serialize<S: Serializer>(
+		datetime: &DateTime<Utc>,
+		serializer: S,
+	) -> Result<S::Ok, S::Error> {
+		serializer.serialize_i64(datetime.timestamp())
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(
+		deserializer: D,
+	) -> Result<DateTime<Utc>, D::Error> {
+		let seconds = i64::deserialize(deserializer)?;
+
+		DateTime::from_timestamp(seconds, 0)
+			.ok_or_else(|| serde::de::Error::custom(format!("timestamp {seconds} out of range")))
+	}
+}
+
+impl FetchedPost {
+	/// [`FetchedPost::timestamp`] as a `DateTime`
+	#[inline]
+	pub fn datetime(&self) -> DateTime<Utc> {
+		from_epoch_seconds(self.timestamp)
+	}
+}
+
+impl BlogInfo {
+	/// [`BlogInfo::updated`] as a `DateTime`
+	#[inline]
+	pub fn updated_datetime(&self) -> DateTime<Utc> {
+		from_epoch_seconds(self.updated)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde::{Deserialize, Serialize};
+
+	use super::*;
+
+	#[derive(Serialize, Deserialize, Debug, PartialEq)]
+	struct Scheduled {
+		#[serde(with = "epoch_seconds")]
+		publish_on: DateTime<Utc>,
+	}
+
+	#[test]
+	fn test_epoch_seconds() {
+		let expected = DateTime::parse_from_rfc3339("2022-06-01T00:00:00Z")
+			.unwrap()
+			.with_timezone(&Utc);
+
+		assert_eq!(from_epoch_seconds(1654041600), expected);
+
+		let scheduled: Scheduled = serde_json::from_str(r#"{"publish_on":1654041600}"#).unwrap();
+		assert_eq!(scheduled.publish_on, expected);
+		assert_eq!(
+			serde_json::to_string(&scheduled).unwrap(),
+			r#"{"publish_on":1654041600}"#
+		);
+
+		let post: FetchedPost =
+			serde_json::from_str(r#"{"id": 1, "blog_name": "staff", "timestamp": 1654041600}"#)
+				.unwrap();
+		assert_eq!(post.datetime(), expected);
+	}
+}
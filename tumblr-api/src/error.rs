@@ -1,3 +1,6 @@
+use std::sync::Arc;
+
+use serde::{ser::SerializeStruct, Serialize, Serializer};
 use thiserror::Error;
 
 use crate::ResponseMeta;
@@ -6,14 +9,137 @@ use crate::ResponseMeta;
 pub enum Error {
 	#[error("Tumblr API error: {0:?}")]
 	Tumblr(ResponseMeta),
-	#[error("HTTP error")]
-	Http(#[from] reqwest::Error),
-	#[error("Error parsing URL")]
+	#[error("User denied access to the application")]
+	AuthDenied,
+	/// an OAuth token endpoint rejected the request, e.g. because of an invalid consumer key
+	#[error("OAuth token exchange failed with status {status}: {body}")]
+	TokenExchangeFailed { status: u16, body: String },
+	#[error("Invalid input: {0}")]
+	Validation(String),
+	/// the resource hasn't changed since the last request, see [`ClientBuilder::conditional_requests`](crate::ClientBuilder::conditional_requests)
+	#[error("Not modified since the last request")]
+	NotModified,
+	#[error("Search is disabled for blog {0}")]
+	SearchDisabled(String),
+	#[error("Blog {0} doesn't share its likes")]
+	LikesNotShared(String),
+	/// none of the user's blogs is marked primary, see [`Client::primary_blog`](crate::Client::primary_blog)
+	#[error("User has no primary blog")]
+	NoPrimaryBlog,
+	/// the request took longer than its timeout, see [`ClientBuilder::timeout`](crate::ClientBuilder::timeout)
+	#[error("Request timed out")]
+	Timeout,
+	#[error("HTTP error: {0}")]
+	Http(#[source] reqwest::Error),
+	#[error("I/O error: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("Error parsing URL: {0}")]
 	UrlParse(#[from] url::ParseError),
-	#[error("Error deserializing response JSON")]
+	/// a request body couldn't be serialized, e.g. a post with a map that doesn't have string keys
+	#[error("Error serializing request JSON: {0}")]
+	Serialize(#[source] serde_json::Error),
+	#[error("Error deserializing response JSON: {0}")]
 	DeserializeJson(#[from] serde_json::Error),
-	#[error("Error deserializing response form data")]
+	#[error("Error deserializing response form data: {0}")]
 	DeserializeForm(#[from] serde_urlencoded::de::Error),
+	#[error("Error serializing query string: {0}")]
+	SerializeForm(#[from] serde_urlencoded::ser::Error),
+	/// an error from a request shared with other callers, see [`ClientBuilder::coalesce_requests`](crate::ClientBuilder::coalesce_requests)
+	#[error(transparent)]
+	Shared(Arc<Error>),
+}
+
+impl From<reqwest::Error> for Error {
+	fn from(error: reqwest::Error) -> Self {
+		if error.is_timeout() {
+			Self::Timeout
+		} else {
+			Self::Http(error)
+		}
+	}
+}
+
+/// Serializes as `{ "kind", "message", "meta" }` for structured logging.
+/// `message` includes the underlying error's message, and `meta` is only present for [`Error::Tumblr`]
+impl Serialize for Error {
+	fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+		let kind = match self {
+			Self::Shared(error) => return error.serialize(serializer),
+			Self::Tumblr(_) => "tumblr",
+			Self::AuthDenied => "auth_denied",
+			Self::TokenExchangeFailed { .. } => "token_exchange_failed",
+			Self::Validation(_) => "validation",
+			Self::NotModified => "not_modified",
+			Self::SearchDisabled(_) => "search_disabled",
+			Self::LikesNotShared(_) => "likes_not_shared",
+			Self::NoPrimaryBlog => "no_primary_blog",
+			Self::Timeout => "timeout",
+			Self::Http(_) => "http",
+			Self::Io(_) => "io",
+			Self::UrlParse(_) => "url_parse",
+			Self::Serialize(_) => "serialize",
+			Self::DeserializeJson(_) => "deserialize_json",
+			Self::DeserializeForm(_) => "deserialize_form",
+			Self::SerializeForm(_) => "serialize_form",
+		};
+
+		let mut state = serializer.serialize_struct("Error", 3)?;
+		state.serialize_field("kind", kind)?;
+		state.serialize_field("message", &self.to_string())?;
+		match self {
+			Self::Tumblr(meta) => state.serialize_field("meta", meta)?,
+			_ => state.skip_field("meta")?,
+		}
+		state.end()
+	}
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	#[test]
+	fn test_http_error_source() {
+		let inner = reqwest::Client::new().get("not a url").build().unwrap_err();
+		let inner_message = inner.to_string();
+
+		let error = Error::from(inner);
+
+		assert_eq!(error.to_string(), format!("HTTP error: {inner_message}"));
+
+		let source = std::error::Error::source(&error).unwrap();
+		assert!(source.is::<reqwest::Error>());
+		assert_eq!(source.to_string(), inner_message);
+	}
+
+	#[test]
+	fn test_serialize_errors() {
+		let error = Error::Tumblr(ResponseMeta {
+			status: 404,
+			msg: "Not Found".to_owned(),
+		});
+
+		assert_eq!(
+			serde_json::to_value(&error).unwrap(),
+			json!({
+				"kind": "tumblr",
+				"message": r#"Tumblr API error: ResponseMeta { status: 404, msg: "Not Found" }"#,
+				"meta": { "status": 404, "msg": "Not Found" },
+			})
+		);
+
+		let error = Error::from(serde_json::from_str::<u64>("x").unwrap_err());
+
+		assert_eq!(
+			serde_json::to_value(Error::Shared(Arc::new(error))).unwrap(),
+			json!({
+				"kind": "deserialize_json",
+				"message": "Error deserializing response JSON: expected value at line 1 column 1",
+			})
+		);
+	}
+}
@@ -0,0 +1,24 @@
+use std::{collections::HashMap, sync::Mutex};
+
+/// The last `ETag` the API sent for each URL
+#[derive(Debug, Default)]
+pub(crate) struct ETags {
+	inner: Mutex<HashMap<String, String>>,
+}
+
+impl ETags {
+	pub(crate) fn get(&self, url: &str) -> Option<String> {
+		self.inner
+			.lock()
+			.expect("etags poisoned")
+			.get(url)
+			.cloned()
+	}
+
+	pub(crate) fn insert(&self, url: String, etag: String) {
+		self.inner
+			.lock()
+			.expect("etags poisoned")
+			.insert(url, etag);
+	}
+}
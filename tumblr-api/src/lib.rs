@@ -1,67 +1,400 @@
-pub use client::{Authenticated, Client, Temporary, Unauthenticated};
+pub use client::{AuthStateKind, Authenticated, Client, ClientBuilder, Temporary, Unauthenticated};
 pub use error::Error;
+pub use metrics::{Metrics, NoopMetrics};
 pub use oauth::OAuthCredentials;
 
-use reqwest::{Method, Url};
-use serde::{de::DeserializeOwned, Deserialize};
+pub use reqwest::{header::HeaderMap, Method};
 
-use self::post::Post;
+use std::time::Duration;
+
+use reqwest::{
+	header::{AUTHORIZATION, ETAG, IF_NONE_MATCH},
+	StatusCode, Url,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use self::{
+	blog::{BlogIdentifier, BlogInfo, BlogInfoResponse, GetPostsOptions},
+	post::{
+		CommunityLabel, CreatedPost, FetchedPost, Post, PostState, PostsPage, Tag, TrailEntry,
+		TrailMode,
+	},
+};
 
 pub mod blog;
+mod cache;
 mod client;
+mod coalesce;
+#[cfg(feature = "chrono")]
+pub mod datetime;
 mod error;
+mod etag;
+mod metrics;
 pub mod oauth;
+#[cfg(feature = "open-graph")]
+mod open_graph;
 pub mod post;
+mod retry;
+#[cfg(feature = "sanitize")]
+pub mod sanitize;
+pub mod upload;
+pub mod user;
 
 pub use error::Result;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ResponseMeta {
 	pub status: u16,
 	pub msg: String,
 }
 
+impl ResponseMeta {
+	/// Classifies the status and message into the failures callers usually handle, see [`MetaKind`]
+	pub fn kind(&self) -> MetaKind {
+		match (self.status, self.msg.to_ascii_lowercase().as_str()) {
+			(401, "not authorized" | "unauthorized") => MetaKind::NotAuthorized,
+			(403, "forbidden") => MetaKind::Forbidden,
+			(404, "not found") => MetaKind::NotFound,
+			(429, "limit exceeded") => MetaKind::LimitExceeded,
+			(400, "bad request") => MetaKind::BadRequest,
+			(500..=599, _) => MetaKind::ServerError,
+			_ => MetaKind::Other(self.msg.clone()),
+		}
+	}
+}
+
+/// A known [`ResponseMeta`] status and message, see [`ResponseMeta::kind`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetaKind {
+	/// the OAuth token is missing, invalid or revoked
+	NotAuthorized,
+	Forbidden,
+	NotFound,
+	/// rate limited, retry later
+	LimitExceeded,
+	BadRequest,
+	/// any 5xx, usually worth retrying
+	ServerError,
+	/// any other status, with its message
+	Other(String),
+}
+
+/// Most listing endpoints return between 1 and this many posts
+pub const MAX_LIMIT: u32 = 20;
+
+/// How many times [`Client::create_and_fetch`] tries to fetch the new post
+const CREATE_AND_FETCH_ATTEMPTS: u32 = 4;
+
+/// How long [`Client::create_and_fetch`] waits after the first `404`, doubling after each one
+const CREATE_AND_FETCH_DELAY: Duration = Duration::from_millis(250);
+
+/// Rejects a `limit` outside `1..=MAX_LIMIT`, which the API would otherwise answer with an opaque 400
+pub(crate) fn check_limit(limit: Option<u32>) -> Result<()> {
+	match limit {
+		Some(limit) if !(1..=MAX_LIMIT).contains(&limit) => Err(Error::Validation(format!(
+			"limit must be between 1 and {MAX_LIMIT}, got {limit}"
+		))),
+		_ => Ok(()),
+	}
+}
+
+/// Appends `options` to `path` as a query string, if there are any
+pub(crate) fn with_query(path: String, options: &impl Serialize) -> Result<String> {
+	let query = serde_urlencoded::to_string(options)?;
+
+	if query.is_empty() {
+		Ok(path)
+	} else if path.contains('?') {
+		Ok(format!("{path}&{query}"))
+	} else {
+		Ok(format!("{path}?{query}"))
+	}
+}
+
+/// Serializes a request body, returning [`Error::Serialize`] rather than [`Error::DeserializeJson`] if it fails
+pub(crate) fn to_json(value: &impl Serialize) -> Result<serde_json::Value> {
+	serde_json::to_value(value).map_err(Error::Serialize)
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct TaggedOptions {
+	/// only posts published before this time, in seconds from epoch
+	pub before: Option<u64>,
+	/// number of results to return, 1-20
+	pub limit: Option<u32>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct FetchPostOptions {
+	pub trail_mode: Option<TrailMode>,
+	/// include where the post was reblogged from, see [`FetchedPost::reblog_info`]
+	pub reblog_info: Option<bool>,
+}
+
+impl TaggedOptions {
+	/// Returns [`Error::Validation`] if `limit` is out of range.  Called before every request
+	pub fn validate(&self) -> Result<()> {
+		check_limit(self.limit)
+	}
+}
+
+/// How a request's body is encoded, see [`Client::request`]
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Body<'a> {
+	/// for the NPF endpoints, e.g. creating a post
+	Json(&'a serde_json::Value),
+	/// for the legacy endpoints, e.g. deleting a post.  Must be an object of plain values
+	Form(&'a serde_json::Value),
+}
+
 #[derive(Deserialize, Debug)]
 struct Response<T> {
 	meta: ResponseMeta,
 	response: Option<T>,
 }
 
-impl Client<Authenticated> {
-	pub const API_BASE: &'static str = "https://api.tumblr.com/v2";
+/// Splits a response body into its `meta` and `response`, or an [`Error::Tumblr`] if it has an error status
+fn parse_response<T: DeserializeOwned>(text: &str) -> Result<(ResponseMeta, T)> {
+	// error responses send `"response": []`, so check the status before parsing the body as `T`
+	let res: Response<serde_json::Value> = serde_json::from_str(text)?;
 
-	pub(crate) async fn request<T: DeserializeOwned>(
+	if (200..300).contains(&res.meta.status) {
+		let response = serde_json::from_value(res.response.unwrap_or(serde_json::Value::Null))?;
+
+		Ok((res.meta, response))
+	} else {
+		Err(Error::Tumblr(res.meta))
+	}
+}
+
+/// Public reads, authenticated with just the application's consumer key
+impl Client<Unauthenticated> {
+	/// `GET`s `path` (relative to the API base) with the consumer key as the `api_key` parameter
+	pub(crate) async fn get_public<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+		let mut url: Url = format!("{}/{}", self.api_base, path).parse()?;
+		url.query_pairs_mut().append_pair("api_key", &self.oauth_consumer_key);
+
+		let res = self.execute(self.client.get(url).build()?).await?;
+
+		parse_response(&res.text().await?).map(|(_, response)| response)
+	}
+
+	/// Makes a two-legged OAuth request to `path` (relative to the API base), signed with only the application's keys.
+	/// For endpoints that need a signature but not a user
+	pub async fn request_two_legged<T: DeserializeOwned>(
 		&self,
 		method: Method,
 		path: &str,
-		data: Option<()>,
+		data: Option<&serde_json::Value>,
 	) -> Result<T> {
-		let url: Url = format!("{}/{}", Self::API_BASE, path).parse()?;
+		let url: Url = format!("{}/{}", self.api_base, path).parse()?;
 
 		let mut req = self.client.request(method, url);
 
 		if let Some(data) = data {
-			todo!()
+			req = req.json(data);
 		}
 
-		let req = req.build()?.sign(
-			&self.oauth_consumer_key,
-			&self.oauth_client_secret,
-			Some(&self.credentials.oauth_token),
-			Some(&self.credentials.oauth_token_secret),
-			vec![],
-		);
+		let res = oauth::ResignableRequest::new(self, req.build()?)
+			.sign_and_send(None, None, None)
+			.await?;
+
+		parse_response(&res.text().await?).map(|(_, response)| response)
+	}
+
+	pub async fn blog_info(&self, blog_identifier: &str) -> Result<BlogInfo> {
+		let blog_identifier = BlogIdentifier::parse(blog_identifier).to_path_segment();
+
+		let BlogInfoResponse { blog } = self
+			.get_public(&format!("blog/{blog_identifier}/info"))
+			.await?;
+
+		Ok(blog)
+	}
+
+	pub async fn get_posts(
+		&self,
+		blog_identifier: &str,
+		options: &GetPostsOptions,
+	) -> Result<PostsPage> {
+		options.validate()?;
+
+		let blog_identifier = BlogIdentifier::parse(blog_identifier).to_path_segment();
+
+		self.get_public(&with_query(format!("blog/{blog_identifier}/posts"), options)?)
+			.await
+	}
+
+	/// Public posts tagged `tag` from every blog, newest first
+	pub async fn tagged(&self, tag: &Tag, options: &TaggedOptions) -> Result<Vec<FetchedPost>> {
+		options.validate()?;
+
+		self.get_public(&with_query(
+			format!("tagged?tag={}", oauth::oauth_encode(tag.as_str())),
+			options,
+		)?)
+		.await
+	}
+}
+
+impl Client<Authenticated> {
+	/// Makes a signed request to `path` (relative to the API base), sending `body` as JSON or as a form
+	pub(crate) async fn request<T: DeserializeOwned>(
+		&self,
+		method: Method,
+		path: &str,
+		body: Option<Body<'_>>,
+	) -> Result<T> {
+		self.request_with_options(method, path, body, &HeaderMap::new(), None)
+			.await
+			.map(|(_, response)| response)
+	}
+
+	/// Makes a signed request to `path` (relative to the API base) and returns the response's `meta` along with its body.
+	/// Useful for endpoints that signal success through `meta` or send nothing in `response` (`T = ()`)
+	pub async fn request_full<T: DeserializeOwned>(
+		&self,
+		method: Method,
+		path: &str,
+		data: Option<&serde_json::Value>,
+	) -> Result<(ResponseMeta, T)> {
+		self.request_with_headers(method, path, data, &HeaderMap::new())
+			.await
+	}
+
+	/// Like [`Client::request_full`], but also sends `extra_headers` (e.g., a beta `X-Version`).
+	/// An `Authorization` header in `extra_headers` is ignored so it can't replace the OAuth signature
+	pub async fn request_with_headers<T: DeserializeOwned>(
+		&self,
+		method: Method,
+		path: &str,
+		data: Option<&serde_json::Value>,
+		extra_headers: &HeaderMap,
+	) -> Result<(ResponseMeta, T)> {
+		self.request_with_options(method, path, data.map(Body::Json), extra_headers, None)
+			.await
+	}
+
+	/// Like [`Client::request_full`], but fails with [`Error::Timeout`] after `timeout` instead of the client's default,
+	/// e.g. to allow a long upload or to give up early on a quick lookup
+	pub async fn request_with_timeout<T: DeserializeOwned>(
+		&self,
+		method: Method,
+		path: &str,
+		data: Option<&serde_json::Value>,
+		timeout: Duration,
+	) -> Result<(ResponseMeta, T)> {
+		self.request_with_options(
+			method,
+			path,
+			data.map(Body::Json),
+			&HeaderMap::new(),
+			Some(timeout),
+		)
+		.await
+	}
+
+	async fn request_with_options<T: DeserializeOwned>(
+		&self,
+		method: Method,
+		path: &str,
+		body: Option<Body<'_>>,
+		extra_headers: &HeaderMap,
+		timeout: Option<Duration>,
+	) -> Result<(ResponseMeta, T)> {
+		let url: Url = format!("{}/{}", self.api_base, path).parse()?;
+
+		let mut req = self.client.request(method.clone(), url.clone());
+
+		// form parameters are part of the OAuth signature, see `oauth::Request::sign`
+		match body {
+			Some(Body::Json(data)) => req = req.json(data),
+			Some(Body::Form(data)) => req = req.form(data),
+			None => (),
+		}
+
+		let is_get = method == Method::GET;
+
+		let etags = self.etags.as_ref().filter(|_| is_get);
+		if let Some(etag) = etags.and_then(|etags| etags.get(url.as_str())) {
+			req = req.header(IF_NONE_MATCH, etag);
+		}
 
-		let res = self.client.execute(req).await?;
-		let text = res.text().await?;
+		if let Some(timeout) = timeout {
+			req = req.timeout(timeout);
+		}
 
-		let res: Response<T> = serde_json::from_str(&text)?;
+		let mut req = req.build()?;
 
-		if res.meta.status == 200 {
-			Ok(res.response.unwrap())
-		} else {
-			Err(Error::Tumblr(res.meta))
+		for (name, value) in extra_headers {
+			if name != AUTHORIZATION {
+				req.headers_mut().append(name, value.clone());
+			}
 		}
+
+		let client = self.clone();
+		let etag_url = url.to_string();
+		let send = async move {
+			let credentials = &client.state().0;
+
+			let res = oauth::ResignableRequest::new(&client, req)
+				.sign_and_send(
+					Some(&credentials.oauth_token),
+					Some(&credentials.oauth_token_secret),
+					None,
+				)
+				.await?;
+
+			if let Some(etags) = client.etags.as_ref().filter(|_| is_get) {
+				if res.status() == StatusCode::NOT_MODIFIED {
+					return Err(Error::NotModified);
+				}
+
+				if let Some(etag) = res.headers().get(ETAG).and_then(|etag| etag.to_str().ok()) {
+					etags.insert(etag_url, etag.to_owned());
+				}
+			}
+
+			Ok(res.text().await?)
+		};
+
+		let text = match &self.coalescer {
+			Some(coalescer) if is_get => {
+				let key = format!("{method} {url} {extra_headers:?} {timeout:?}");
+				coalescer.run(key, send).await?.as_ref().clone()
+			}
+			_ => send.await?,
+		};
+
+		parse_response(&text)
+	}
+
+	/// Builds and signs the request [`Client::request_full`] would send to `path`, without sending it.
+	/// For inspecting the `Authorization` header and body when debugging a signature mismatch.
+	/// Each call signs with a fresh nonce and timestamp
+	pub fn build_signed_request(
+		&self,
+		method: Method,
+		path: &str,
+		data: Option<&serde_json::Value>,
+	) -> Result<reqwest::Request> {
+		let url: Url = format!("{}/{}", self.api_base, path).parse()?;
+
+		let mut req = self.client.request(method, url);
+
+		if let Some(data) = data {
+			req = req.json(data);
+		}
+
+		let credentials = &self.state().0;
+
+		oauth::ResignableRequest::new(self, req.build()?).sign(
+			Some(&credentials.oauth_token),
+			Some(&credentials.oauth_token_secret),
+			None,
+		)
 	}
 
 	pub(crate) async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
@@ -71,22 +404,1325 @@ impl Client<Authenticated> {
 	pub(crate) async fn post<T: DeserializeOwned>(
 		&self,
 		path: &str,
-		data: Option<()>,
+		data: Option<&serde_json::Value>,
+	) -> Result<T> {
+		self.request(Method::POST, path, data.map(Body::Json)).await
+	}
+
+	/// `POST`s `form` to one of the legacy endpoints, which don't accept JSON
+	pub(crate) async fn post_form<T: DeserializeOwned>(
+		&self,
+		path: &str,
+		form: &serde_json::Value,
 	) -> Result<T> {
-		self.request(Method::POST, path, data).await
+		self.request(Method::POST, path, Some(Body::Form(form)))
+			.await
 	}
 
 	pub async fn blog_info(&self, blog_identifier: &str) -> Result<serde_json::Value> {
+		let blog_identifier = BlogIdentifier::parse(blog_identifier).to_path_segment();
+
 		self.request(Method::GET, &format!("blog/{blog_identifier}/info"), None)
 			.await
 	}
 
-	pub async fn get_post(&self, post_id: u64) -> Result<serde_json::Value> {
+	/// Fetches a post through the legacy `posts/{post_id}` endpoint, which may omit NPF content and the reblog key.
+	/// Prefer [`Client::fetch_post`]
+	pub async fn get_post(&self, post_id: u64) -> Result<FetchedPost> {
 		self.request(Method::GET, &format!("posts/{post_id}"), None)
 			.await
 	}
 
-	pub async fn create_post(&self, blog_name: &str, post: Post) -> Result<bool> {
-		todo!()
+	/// Fetches a post in NPF format, including its `reblog_key` and typed reblog [`trail`](FetchedPost::trail)
+	pub async fn fetch_post(
+		&self,
+		blog_identifier: &str,
+		post_id: u64,
+		options: &FetchPostOptions,
+	) -> Result<FetchedPost> {
+		let blog_identifier = BlogIdentifier::parse(blog_identifier).to_path_segment();
+
+		self.get(&with_query(
+			format!("blog/{blog_identifier}/posts/{post_id}"),
+			options,
+		)?)
+		.await
+	}
+
+	/// Fetches a post and returns its reblog chain, from the root post to the post itself.
+	/// Entries whose blog or post has since been deleted are kept and marked [`TrailEntry::broken`]
+	pub async fn reblog_ancestry(
+		&self,
+		blog_identifier: &str,
+		post_id: u64,
+	) -> Result<Vec<TrailEntry>> {
+		Ok(self
+			.fetch_post(blog_identifier, post_id, &FetchPostOptions::default())
+			.await?
+			.into_ancestry())
+	}
+
+	pub async fn create_post(&self, blog_name: &str, post: Post) -> Result<CreatedPost> {
+		let blog_identifier = BlogIdentifier::parse(blog_name).to_path_segment();
+
+		self.post(
+			&format!("blog/{blog_identifier}/posts"),
+			Some(&post.to_body()?),
+		)
+		.await
+	}
+
+	/// Creates `post` and fetches it back, for showing it right away.
+	/// A new post can take a moment to become readable, so a `404` is retried a few times, waiting longer each time
+	pub async fn create_and_fetch(&self, blog_name: &str, post: Post) -> Result<FetchedPost> {
+		let created = self.create_post(blog_name, post).await?;
+
+		let mut delay = CREATE_AND_FETCH_DELAY;
+		for _ in 1..CREATE_AND_FETCH_ATTEMPTS {
+			match self
+				.fetch_post(blog_name, created.id, &FetchPostOptions::default())
+				.await
+			{
+				Err(Error::Tumblr(meta)) if meta.status == 404 => {
+					tokio::time::sleep(delay).await;
+					delay *= 2;
+				}
+				other => return other,
+			}
+		}
+
+		self.fetch_post(blog_name, created.id, &FetchPostOptions::default())
+			.await
+	}
+
+	/// Reblogs `parent` to `blog_name`, with `post`'s content as the reblog's own comment.
+	/// [`Post::state`] and [`Post::publish_on`] apply as for a new post, so a reblog can be queued or saved as a draft.
+	/// Returns [`Error::Validation`] if `parent` is missing its `reblog_key` or blog UUID, which some endpoints leave out
+	pub async fn reblog(
+		&self,
+		blog_name: &str,
+		parent: &FetchedPost,
+		post: Post,
+	) -> Result<CreatedPost> {
+		let (Some(reblog_key), Some(parent_blog)) = (&parent.reblog_key, &parent.blog) else {
+			return Err(Error::Validation(format!(
+				"post {} is missing its reblog key or blog, fetch it with Client::fetch_post",
+				parent.id
+			)));
+		};
+
+		let blog_identifier = BlogIdentifier::parse(blog_name).to_path_segment();
+
+		let mut body = post.to_body()?;
+		if let serde_json::Value::Object(body) = &mut body {
+			body.insert(
+				"parent_tumblelog_uuid".to_owned(),
+				parent_blog.uuid.clone().into(),
+			);
+			body.insert("parent_post_id".to_owned(), parent.id.to_string().into());
+			body.insert("reblog_key".to_owned(), reblog_key.clone().into());
+		}
+
+		self.post(&format!("blog/{blog_identifier}/posts"), Some(&body))
+			.await
+	}
+
+	/// Publishes `post` as the answer to the ask `ask_post_id` in `blog_name`'s inbox, see [`Post::answer_ask`]
+	pub async fn answer_ask(
+		&self,
+		blog_name: &str,
+		ask_post_id: u64,
+		post: Post,
+	) -> Result<CreatedPost> {
+		let blog_identifier = BlogIdentifier::parse(blog_name).to_path_segment();
+
+		let mut body = post.to_body()?;
+		if let serde_json::Value::Object(body) = &mut body {
+			body.insert("state".to_owned(), "published".into());
+		}
+
+		self.request(
+			Method::PUT,
+			&format!("blog/{blog_identifier}/posts/{ask_post_id}"),
+			Some(Body::Json(&body)),
+		)
+		.await
+	}
+
+	/// Replaces only the tags of post `post_id`, keeping its content, layout, slug, state and community labels as fetched
+	pub async fn edit_post_tags(
+		&self,
+		blog_name: &str,
+		post_id: u64,
+		tags: Vec<Tag>,
+	) -> Result<CreatedPost> {
+		self.edit_fetched_post(blog_name, post_id, |post| post.tags = Some(tags))
+			.await
+	}
+
+	/// Replaces only the community labels of post `post_id`, keeping its content, layout, slug, tags and state as fetched
+	pub async fn edit_post_labels(
+		&self,
+		blog_name: &str,
+		post_id: u64,
+		labels: Vec<CommunityLabel>,
+	) -> Result<CreatedPost> {
+		self.edit_fetched_post(blog_name, post_id, |post| {
+			post.community_labels = Some(labels)
+		})
+		.await
+	}
+
+	/// Moves post `post_id` to `state` (e.g. publishes a draft), keeping its content, layout, slug, tags and community labels as fetched
+	pub async fn edit_post_state(
+		&self,
+		blog_name: &str,
+		post_id: u64,
+		state: PostState,
+	) -> Result<CreatedPost> {
+		self.edit_fetched_post(blog_name, post_id, |post| post.state = Some(state))
+			.await
+	}
+
+	/// Fetches post `post_id`, applies `edit` to a [`Post`] built from it, and sends the whole post back,
+	/// since the edit endpoint replaces every field it isn't sent.
+	/// The fetched state, schedule and privacy are sent back too, so e.g. a draft isn't published by editing its tags.
+	/// Reblogs are sent with their parent and `reblog_key` so they stay attached to the reblog trail
+	async fn edit_fetched_post(
+		&self,
+		blog_name: &str,
+		post_id: u64,
+		edit: impl FnOnce(&mut Post),
+	) -> Result<CreatedPost> {
+		// the parent is only sent with `reblog_info`
+		let options = FetchPostOptions {
+			reblog_info: Some(true),
+			..Default::default()
+		};
+		let fetched = self.fetch_post(blog_name, post_id, &options).await?;
+
+		let reblog_info = &fetched.reblog_info;
+		let parent = match (
+			reblog_info.reblogged_from_uuid.clone(),
+			reblog_info.reblogged_from_id,
+			fetched.reblog_key.clone(),
+		) {
+			(Some(parent_uuid), Some(parent_id), Some(reblog_key)) => Some(
+				[
+					("parent_tumblelog_uuid".to_owned(), parent_uuid.into()),
+					("parent_post_id".to_owned(), parent_id.to_string().into()),
+					("reblog_key".to_owned(), reblog_key.into()),
+				]
+				.into(),
+			),
+			_ => None,
+		};
+
+		let state = fetched.post_state();
+		// `publish_on` is only allowed for queued posts
+		let publish_on = fetched
+			.publish_on()
+			.filter(|_| state == Some(PostState::Queue));
+
+		let mut post = Post {
+			content: fetched.content,
+			layout: (!fetched.layout.is_empty()).then_some(fetched.layout),
+			slug: fetched.slug,
+			state,
+			publish_on,
+			is_private: fetched.is_private,
+			tags: Some(fetched.tags),
+			community_labels: fetched.community_labels,
+			post_options: parent,
+			..Default::default()
+		};
+		edit(&mut post);
+
+		if post.state != Some(PostState::Queue) {
+			post.publish_on = None;
+		}
+
+		let blog_identifier = BlogIdentifier::parse(blog_name).to_path_segment();
+
+		self.request(
+			Method::PUT,
+			&format!("blog/{blog_identifier}/posts/{post_id}"),
+			Some(Body::Json(&post.to_body()?)),
+		)
+		.await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+	use wiremock::{
+		matchers::{body_json, body_string, header, header_exists, method, path, query_param},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	use std::time::Duration;
+
+	use crate::{
+		blog::GetPostsOptions,
+		client::tests::{authenticated, mock_builder, mock_client, ok_response},
+		oauth::tests::assert_valid_signature,
+		post::{
+			CommunityLabel, ContentBlock, FetchedPost, Post, PostState, ReblogTrail, Tag, TrailMode,
+		},
+		Client, Error, FetchPostOptions, HeaderMap, MetaKind, Method, ResponseMeta,
+	};
+
+	#[test]
+	fn test_serialize_error() {
+		let unserializable: std::collections::HashMap<_, _> = [((1, 2), "tuple keys")].into();
+
+		let error = crate::to_json(&unserializable).unwrap_err();
+		assert!(matches!(error, Error::Serialize(_)));
+		assert_eq!(serde_json::to_value(&error).unwrap()["kind"], "serialize");
+
+		assert!(crate::to_json(&Post::default()).is_ok());
+	}
+
+	#[test]
+	fn test_meta_kind() {
+		let kind = |status, msg: &str| {
+			ResponseMeta {
+				status,
+				msg: msg.to_owned(),
+			}
+			.kind()
+		};
+
+		assert_eq!(kind(401, "Not Authorized"), MetaKind::NotAuthorized);
+		assert_eq!(kind(404, "Not Found"), MetaKind::NotFound);
+		assert_eq!(kind(429, "Limit Exceeded"), MetaKind::LimitExceeded);
+		assert_eq!(kind(503, "Service Unavailable"), MetaKind::ServerError);
+		assert_eq!(
+			kind(404, "Blog not found"),
+			MetaKind::Other("Blog not found".to_owned())
+		);
+	}
+
+	#[tokio::test]
+	async fn test_coalesces_identical_gets() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.respond_with(ok_response("{}").set_delay(Duration::from_millis(200)))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let client = authenticated(mock_builder(&server).coalesce_requests(true));
+
+		let (a, b, c) = tokio::join!(
+			client.blog_info("staff"),
+			client.blog_info("staff"),
+			client.blog_info("staff"),
+		);
+
+		assert_eq!(a.unwrap(), json!({}));
+		assert_eq!(b.unwrap(), json!({}));
+		assert_eq!(c.unwrap(), json!({}));
+		assert_eq!(client.coalescer.as_ref().unwrap().len(), 0);
+	}
+
+	#[tokio::test]
+	async fn test_public_blog_info() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.and(query_param("api_key", "consumer-key"))
+			.respond_with(ok_response(
+				r#"{
+	"blog": {
+		"title": "Tumblr Staff",
+		"posts": 6500,
+		"name": "staff",
+		"updated": 1654041600,
+		"description": "The official Tumblr staff blog",
+		"ask": true,
+		"ask_anon": false
+	}
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let info = mock_builder(&server)
+			.build()
+			.blog_info("staff")
+			.await
+			.unwrap();
+
+		assert_eq!(info.name, "staff");
+		assert_eq!(info.posts, 6500);
+	}
+
+	#[tokio::test]
+	async fn test_request_two_legged() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.and(header_exists("authorization"))
+			.respond_with(ok_response("{}"))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let response: serde_json::Value = mock_builder(&server)
+			.build()
+			.request_two_legged(Method::GET, "blog/staff/info", None)
+			.await
+			.unwrap();
+
+		assert_eq!(response, json!({}));
+	}
+
+	#[tokio::test]
+	async fn test_public_get_posts() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts"))
+			.and(query_param("api_key", "consumer-key"))
+			.and(query_param("limit", "1"))
+			.respond_with(ok_response(
+				r#"{
+	"posts": [{ "id": 1, "blog_name": "staff", "timestamp": 1654041600 }],
+	"total_posts": 6500
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let page = mock_builder(&server)
+			.build()
+			.get_posts(
+				"staff",
+				&GetPostsOptions {
+					limit: Some(1),
+					..Default::default()
+				},
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(page.posts[0].id, 1);
+		assert_eq!(page.total_posts, Some(6500));
+	}
+
+	#[tokio::test]
+	async fn test_conditional_requests() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.and(header("if-none-match", "\"v1\""))
+			.respond_with(ResponseTemplate::new(304))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.respond_with(ok_response("{}").insert_header("etag", "\"v1\""))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let client = authenticated(mock_builder(&server).conditional_requests(true));
+
+		assert_eq!(client.blog_info("staff").await.unwrap(), json!({}));
+		assert!(matches!(
+			client.blog_info("staff").await,
+			Err(Error::NotModified)
+		));
+	}
+
+	#[tokio::test]
+	async fn test_request_with_headers() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.and(header("x-version", "beta"))
+			.and(header_exists("authorization"))
+			.respond_with(ok_response("{}"))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let mut headers = HeaderMap::new();
+		headers.insert("x-version", "beta".parse().unwrap());
+		headers.insert("authorization", "Bearer nope".parse().unwrap());
+
+		let (_, response): (_, serde_json::Value) = mock_client(&server)
+			.request_with_headers(Method::GET, "blog/staff/info", None, &headers)
+			.await
+			.unwrap();
+
+		assert_eq!(response, json!({}));
+
+		let requests = server.received_requests().await.unwrap();
+		let authorization = requests[0]
+			.headers
+			.iter()
+			.find(|(name, _)| name.as_str() == "authorization")
+			.map(|(_, values)| values.iter().map(|value| value.as_str()).collect::<Vec<_>>())
+			.unwrap();
+		assert!(authorization[0].starts_with("OAuth "));
+		assert!(authorization.iter().all(|value| !value.contains("Bearer")));
+	}
+
+	#[tokio::test]
+	async fn test_request_with_timeout() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/info"))
+			.respond_with(ok_response("{}").set_delay(Duration::from_millis(500)))
+			.mount(&server)
+			.await;
+
+		let client = authenticated(mock_builder(&server).timeout(Duration::from_secs(30)));
+
+		let error = client
+			.request_with_timeout::<serde_json::Value>(
+				Method::GET,
+				"blog/staff/info",
+				None,
+				Duration::from_millis(50),
+			)
+			.await
+			.unwrap_err();
+		assert!(matches!(error, Error::Timeout), "{error:?}");
+
+		let (_, info) = client
+			.request_full::<serde_json::Value>(Method::GET, "blog/staff/info", None)
+			.await
+			.unwrap();
+		assert_eq!(info, json!({}));
+	}
+
+	#[test]
+	fn test_build_signed_request() {
+		let client = authenticated(Client::builder(
+			"consumer-key".to_owned(),
+			"client-secret".to_owned(),
+		));
+
+		let req = client
+			.build_signed_request(
+				Method::POST,
+				"blog/staff/posts",
+				Some(&json!({ "content": [] })),
+			)
+			.unwrap();
+
+		assert_eq!(req.url().as_str(), "https://api.tumblr.com/v2/blog/staff/posts");
+		assert_eq!(
+			req.body().and_then(reqwest::Body::as_bytes),
+			Some(&br#"{"content":[]}"#[..])
+		);
+
+		let authorization = req.headers()[reqwest::header::AUTHORIZATION]
+			.to_str()
+			.unwrap()
+			.strip_prefix("OAuth ")
+			.unwrap();
+
+		let params: std::collections::HashMap<_, _> = authorization
+			.split(',')
+			.map(|param| {
+				let (k, v) = param.split_once('=').unwrap();
+				assert!(v.starts_with('"') && v.ends_with('"'), "unquoted {k}");
+				(k, v.trim_matches('"'))
+			})
+			.collect();
+
+		assert_eq!(params["oauth_consumer_key"], "consumer-key");
+		assert_eq!(params["oauth_token"], "token");
+		assert_eq!(params["oauth_signature_method"], "HMAC-SHA1");
+		assert_eq!(params["oauth_version"], "1.0");
+		assert!(!params["oauth_nonce"].is_empty());
+		assert!(params["oauth_timestamp"].parse::<u64>().is_ok());
+		assert!(!params["oauth_signature"].is_empty());
+	}
+
+	#[tokio::test]
+	async fn test_form_and_json_signatures() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("POST"))
+			.and(path("/blog/staff/post/delete"))
+			.and(header("content-type", "application/x-www-form-urlencoded"))
+			.and(body_string("id=1234"))
+			.respond_with(ok_response(r#"{"id":"1234"}"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		Mock::given(method("POST"))
+			.and(path("/blog/staff/posts"))
+			.and(header("content-type", "application/json"))
+			.and(body_json(json!({ "content": [] })))
+			.respond_with(ok_response(r#"{"id":"5678"}"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let client = mock_client(&server);
+
+		client
+			.blog("staff")
+			.unwrap()
+			.delete_post(1234)
+			.await
+			.unwrap();
+		client
+			.create_post("staff", Post::default())
+			.await
+			.unwrap();
+
+		for request in server.received_requests().await.unwrap() {
+			assert_valid_signature(&server, &request);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_request_full_returns_meta() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("POST"))
+			.and(path("/user/like"))
+			.respond_with(ResponseTemplate::new(200).set_body_raw(
+				r#"{"meta":{"status":200,"msg":"Liked"},"response":{"liked":true}}"#,
+				"application/json",
+			))
+			.mount(&server)
+			.await;
+
+		let (meta, response): (_, serde_json::Value) = mock_client(&server)
+			.request_full(Method::POST, "user/like", None)
+			.await
+			.unwrap();
+
+		assert_eq!(meta.status, 200);
+		assert_eq!(meta.msg, "Liked");
+		assert_eq!(response, json!({ "liked": true }));
+	}
+
+	#[tokio::test]
+	async fn test_fetch_post() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts/1"))
+			.respond_with(ok_response(
+				r#"{
+	"id": 1,
+	"blog_name": "staff",
+	"timestamp": 1654041600,
+	"reblog_key": "ABCdef12",
+	"content": [{ "type": "text", "text": "hello" }],
+	"layout": []
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let post = mock_client(&server)
+			.fetch_post("staff", 1, &FetchPostOptions::default())
+			.await
+			.unwrap();
+
+		assert_eq!(post.reblog_key.as_deref(), Some("ABCdef12"));
+		assert_eq!(post.timestamp, 1654041600);
+		assert_eq!(post.content.len(), 1);
+	}
+
+	#[tokio::test]
+	async fn test_fetch_post_trail() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/current/posts/3"))
+			.respond_with(ok_response(
+				r#"{
+	"id": 3,
+	"blog_name": "current",
+	"timestamp": 1654041600,
+	"content": [{ "type": "text", "text": "reblog comment" }],
+	"layout": [],
+	"trail": [
+		{
+			"post": { "id": 1 },
+			"blog": { "uuid": "t:root", "name": "root" },
+			"content": [{ "type": "text", "text": "root post" }],
+			"layout": [{ "type": "rows", "blocks": [{ "blocks": [0] }] }]
+		},
+		{
+			"broken_blog_name": "deleted-blog",
+			"content": [],
+			"layout": []
+		}
+	]
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let post = mock_client(&server)
+			.fetch_post("current", 3, &FetchPostOptions::default())
+			.await
+			.unwrap();
+
+		assert_eq!(post.trail.len(), 2);
+		assert!(matches!(
+			&post.trail[0],
+			ReblogTrail::Ok { post, blog, content, layout }
+				if post.id == 1
+					&& blog.name.as_deref() == Some("root")
+					&& content.len() == 1
+					&& layout.len() == 1
+		));
+		assert!(matches!(
+			&post.trail[1],
+			ReblogTrail::Broken { broken_blog_name, .. } if broken_blog_name == "deleted-blog"
+		));
+	}
+
+	#[tokio::test]
+	async fn test_fetch_post_compact_trail() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/current/posts/3"))
+			.and(query_param("trail_mode", "compact"))
+			.respond_with(ok_response(
+				r#"{
+	"id": 3,
+	"blog_name": "current",
+	"timestamp": 1654041600,
+	"trail": [{ "post": { "id": 1 }, "blog": { "uuid": "t:root", "name": "root" } }]
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let post = mock_client(&server)
+			.fetch_post(
+				"current",
+				3,
+				&FetchPostOptions {
+					trail_mode: Some(TrailMode::Compact),
+					..Default::default()
+				},
+			)
+			.await
+			.unwrap();
+
+		assert!(matches!(
+			&post.trail[0],
+			ReblogTrail::Reference { post, .. } if post.id == 1
+		));
+	}
+
+	#[tokio::test]
+	async fn test_reblog_ancestry() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/current/posts/3"))
+			.respond_with(ok_response(
+				r#"{
+	"id": 3,
+	"blog_name": "current",
+	"blog": { "uuid": "t:current", "name": "current" },
+	"timestamp": 1654041600,
+	"content": [{ "type": "text", "text": "reblog comment" }],
+	"layout": [],
+	"trail": [
+		{
+			"post": { "id": 1 },
+			"blog": { "uuid": "t:root", "name": "root" },
+			"content": [{ "type": "text", "text": "root post" }],
+			"layout": []
+		},
+		{
+			"broken_blog_name": "deleted-blog",
+			"content": [{ "type": "text", "text": "broken middle" }],
+			"layout": []
+		},
+		{
+			"post": { "id": 2 },
+			"blog": { "uuid": "t:parent", "name": "parent" },
+			"content": [{ "type": "text", "text": "parent post" }],
+			"layout": []
+		}
+	]
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let ancestry = mock_client(&server)
+			.reblog_ancestry("current", 3)
+			.await
+			.unwrap();
+
+		assert_eq!(
+			ancestry
+				.iter()
+				.map(|entry| (entry.blog_name.as_deref(), entry.broken))
+				.collect::<Vec<_>>(),
+			vec![
+				(Some("root"), false),
+				(Some("deleted-blog"), true),
+				(Some("parent"), false),
+				(Some("current"), false),
+			]
+		);
+		assert_eq!(ancestry[0].post.as_ref().map(|post| post.id), Some(1));
+		assert_eq!(ancestry[1].post, None);
+		assert_eq!(ancestry[1].blog, None);
+		assert_eq!(ancestry[3].post.as_ref().map(|post| post.id), Some(3));
+		assert_eq!(
+			ancestry[3].blog.as_ref().map(|blog| blog.uuid.as_str()),
+			Some("t:current")
+		);
+	}
+
+	#[tokio::test]
+	async fn test_create_post_sends_body() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("POST"))
+			.and(path("/blog/staff/posts"))
+			.and(body_json(json!({
+				"content": [],
+				"interactability_blaze": "everyone",
+			})))
+			.respond_with(ResponseTemplate::new(201).set_body_raw(
+				r#"{"meta":{"status":201,"msg":"Created"},"response":{"id":"1234567891234567"}}"#,
+				"application/json",
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let created = mock_client(&server)
+			.create_post(
+				"staff",
+				Post {
+					post_options: Some(
+						[("interactability_blaze".to_owned(), json!("everyone"))].into(),
+					),
+					..Default::default()
+				},
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(created.id, 1234567891234567);
+	}
+
+	#[tokio::test]
+	async fn test_reblog_to_queue() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("POST"))
+			.and(path("/blog/staff/posts"))
+			.and(body_json(json!({
+				"content": [{ "type": "text", "text": "so true" }],
+				"state": "queue",
+				"publish_on": "2022-06-01T12:00:00Z",
+				"parent_tumblelog_uuid": "t:parent",
+				"parent_post_id": "1234",
+				"reblog_key": "ABCdef12",
+			})))
+			.respond_with(ResponseTemplate::new(201).set_body_raw(
+				r#"{"meta":{"status":201,"msg":"Created"},"response":{"id":"5678"}}"#,
+				"application/json",
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let parent: FetchedPost = serde_json::from_value(json!({
+			"id": 1234,
+			"blog_name": "parent",
+			"blog": { "uuid": "t:parent", "name": "parent" },
+			"timestamp": 1654041600,
+			"reblog_key": "ABCdef12",
+		}))
+		.unwrap();
+
+		let client = mock_client(&server);
+		let post = || Post {
+			content: vec![
+				serde_json::from_value(json!({ "type": "text", "text": "so true" })).unwrap(),
+			],
+			state: Some(PostState::Queue),
+			publish_on: Some("2022-06-01T12:00:00Z".to_owned()),
+			..Default::default()
+		};
+
+		let created = client.reblog("staff", &parent, post()).await.unwrap();
+		assert_eq!(created.id, 5678);
+
+		let error = client
+			.reblog(
+				"staff",
+				&FetchedPost {
+					reblog_key: None,
+					..parent
+				},
+				post(),
+			)
+			.await
+			.unwrap_err();
+		assert!(matches!(error, Error::Validation(_)));
+	}
+
+	#[tokio::test]
+	async fn test_edit_post_tags_keeps_content() {
+		let server = MockServer::start().await;
+
+		let fetched = json!({
+			"id": 1234,
+			"blog_name": "staff",
+			"timestamp": 1654041600,
+			"slug": "corgi-facts",
+			"reblog_key": "ABCdef12",
+			"tags": ["dogs"],
+			"content": [
+				{ "type": "text", "text": "corgi facts", "subtype": "heading1" },
+				{ "type": "link", "url": "https://example.com/corgis", "display_url": "example.com/corgis" }
+			],
+			"layout": [{ "type": "rows", "blocks": [{ "blocks": [0] }, { "blocks": [1] }] }]
+		});
+
+		// like Tumblr, the parent is only sent when `reblog_info` is asked for
+		let mut with_reblog_info = fetched.clone();
+		with_reblog_info["reblogged_from_id"] = "1000".into();
+		with_reblog_info["reblogged_from_uuid"] = "t:parent".into();
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts/1234"))
+			.and(query_param("reblog_info", "true"))
+			.respond_with(ok_response(&with_reblog_info.to_string()))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts/1234"))
+			.respond_with(ok_response(&fetched.to_string()))
+			.expect(0)
+			.mount(&server)
+			.await;
+
+		Mock::given(method("PUT"))
+			.and(path("/blog/staff/posts/1234"))
+			.and(body_json(json!({
+				"content": [
+					{ "type": "text", "text": "corgi facts", "subtype": "heading1" },
+					{ "type": "link", "url": "https://example.com/corgis" },
+				],
+				"layout": [{ "type": "rows", "blocks": [{ "blocks": [0] }, { "blocks": [1] }] }],
+				"slug": "corgi-facts",
+				"tags": "corgi,dogs",
+				"parent_tumblelog_uuid": "t:parent",
+				"parent_post_id": "1000",
+				"reblog_key": "ABCdef12",
+			})))
+			.respond_with(ok_response(r#"{ "id": "1234" }"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let edited = mock_client(&server)
+			.edit_post_tags("staff", 1234, vec![Tag::parse("#corgi"), Tag::parse("dogs")])
+			.await
+			.unwrap();
+
+		assert_eq!(edited.id, 1234);
+	}
+
+	#[tokio::test]
+	async fn test_edit_post_labels_keeps_content() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts/1234"))
+			.respond_with(ok_response(
+				r#"{
+	"id": 1234,
+	"blog_name": "staff",
+	"timestamp": 1654041600,
+	"slug": "fight-scene",
+	"tags": ["film"],
+	"content": [{ "type": "text", "text": "the fight scene, annotated" }]
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		Mock::given(method("PUT"))
+			.and(path("/blog/staff/posts/1234"))
+			.and(body_json(json!({
+				"content": [{ "type": "text", "text": "the fight scene, annotated" }],
+				"slug": "fight-scene",
+				"tags": "film",
+				"community_labels": ["violence", "drug_use"],
+			})))
+			.respond_with(ok_response(r#"{ "id": "1234" }"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let edited = mock_client(&server)
+			.edit_post_labels(
+				"staff",
+				1234,
+				vec![CommunityLabel::Violence, CommunityLabel::DrugUse],
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(edited.id, 1234);
+	}
+
+	#[tokio::test]
+	async fn test_create_and_fetch() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("POST"))
+			.and(path("/blog/staff/posts"))
+			.respond_with(ok_response(r#"{ "id": "1234" }"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		// not readable yet right after it's created
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts/1234"))
+			.respond_with(ResponseTemplate::new(404).set_body_raw(
+				r#"{"meta":{"status":404,"msg":"Not Found"},"response":[]}"#,
+				"application/json",
+			))
+			.up_to_n_times(1)
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts/1234"))
+			.respond_with(ok_response(
+				r#"{
+	"id": 1234,
+	"blog_name": "staff",
+	"timestamp": 1654041600,
+	"content": [{ "type": "text", "text": "hello" }]
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let hello = || ContentBlock::Text {
+			text: "hello".to_owned(),
+			subtype: None,
+			indent_level: None,
+			formatting: None,
+		};
+
+		let fetched = mock_client(&server)
+			.create_and_fetch(
+				"staff",
+				Post {
+					content: vec![hello()],
+					..Default::default()
+				},
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(fetched.id, 1234);
+		assert_eq!(fetched.blog_name, "staff");
+		assert_eq!(fetched.content, [hello()]);
+	}
+
+	#[tokio::test]
+	async fn test_get_post_round_trip() {
+		let server = MockServer::start().await;
+
+		let captured = json!({
+			"id": 1234,
+			"blog_name": "staff",
+			"blog": { "uuid": "t:staff", "name": "staff" },
+			"timestamp": 1654041600,
+			"state": "published",
+			"slug": "corgi-facts",
+			"reblog_key": "ABCdef12",
+			"content": [{ "type": "text", "text": "corgis have short legs" }],
+			"layout": [{ "type": "rows", "blocks": [{ "blocks": [0] }] }],
+			"trail": [
+				{
+					"post": { "id": 1000 },
+					"blog": { "uuid": "t:root", "name": "root" },
+					"content": [{ "type": "text", "text": "corgi facts", "subtype": "heading1" }],
+					"layout": [],
+				}
+			],
+			"tags": ["corgi", "dogs"],
+			"note_count": 12,
+		});
+
+		Mock::given(method("GET"))
+			.and(path("/posts/1234"))
+			.respond_with(ok_response(&captured.to_string()))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let post = mock_client(&server).get_post(1234).await.unwrap();
+
+		assert_eq!(post.id, 1234);
+		assert_eq!(post.state.as_deref(), Some("published"));
+		assert_eq!(post.tags, [Tag::parse("corgi"), Tag::parse("dogs")]);
+		assert!(matches!(&post.trail[..], [ReblogTrail::Ok { post, .. }] if post.id == 1000));
+		assert!(post.extra.is_empty());
+
+		assert_eq!(serde_json::to_value(&post).unwrap(), captured);
+	}
+
+	#[tokio::test]
+	async fn test_retries_rate_limited() {
+		let server = MockServer::start().await;
+
+		let rate_limited = ResponseTemplate::new(429).set_body_raw(
+			r#"{"meta":{"status":429,"msg":"Limit Exceeded"},"response":[]}"#,
+			"application/json",
+		);
+
+		Mock::given(method("GET"))
+			.and(path("/user/counts"))
+			.respond_with(rate_limited.clone().insert_header("retry-after", "0"))
+			.up_to_n_times(1)
+			.mount(&server)
+			.await;
+
+		// without `Retry-After`, falls back to the base backoff
+		Mock::given(method("GET"))
+			.and(path("/user/counts"))
+			.respond_with(rate_limited)
+			.up_to_n_times(1)
+			.mount(&server)
+			.await;
+
+		Mock::given(method("GET"))
+			.and(path("/user/counts"))
+			.respond_with(ok_response(r#"{ "notifications": 1 }"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let client = authenticated(mock_builder(&server).retry_rate_limited(
+			3,
+			Duration::from_millis(10),
+			Duration::from_secs(1),
+		));
+
+		assert_eq!(client.counts().await.unwrap().notifications, 1);
+
+		let requests = server.received_requests().await.unwrap();
+		assert_eq!(requests.len(), 3);
+
+		let nonces: std::collections::HashSet<_> = requests
+			.iter()
+			.map(|request| {
+				let authorization = request
+					.headers
+					.iter()
+					.find(|(name, _)| name.as_str() == "authorization")
+					.map(|(_, values)| {
+						values
+							.iter()
+							.map(|value| value.as_str())
+							.collect::<Vec<_>>()
+							.join(",")
+					})
+					.unwrap();
+
+				authorization
+					.split(',')
+					.find(|param| param.starts_with("oauth_nonce="))
+					.unwrap()
+					.to_owned()
+			})
+			.collect();
+		assert_eq!(nonces.len(), 3);
+
+		for request in &requests {
+			assert_valid_signature(&server, request);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_rate_limit_retries_exhausted() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/user/counts"))
+			.respond_with(
+				ResponseTemplate::new(429)
+					.set_body_raw(
+						r#"{"meta":{"status":429,"msg":"Limit Exceeded"},"response":[]}"#,
+						"application/json",
+					)
+					.insert_header("retry-after", "0"),
+			)
+			.expect(3)
+			.mount(&server)
+			.await;
+
+		let client = authenticated(mock_builder(&server).retry_rate_limited(
+			2,
+			Duration::from_millis(10),
+			Duration::from_secs(1),
+		));
+
+		let err = client.counts().await.unwrap_err();
+		assert!(matches!(err, Error::Tumblr(meta) if meta.status == 429));
+	}
+
+	#[tokio::test]
+	async fn test_rate_limit_too_long_to_wait() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/user/counts"))
+			.respond_with(
+				ResponseTemplate::new(429)
+					.set_body_raw(
+						r#"{"meta":{"status":429,"msg":"Limit Exceeded"},"response":[]}"#,
+						"application/json",
+					)
+					.insert_header("retry-after", "86400"),
+			)
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let client = authenticated(mock_builder(&server).retry_rate_limited(
+			3,
+			Duration::from_millis(10),
+			Duration::from_secs(60),
+		));
+
+		// gives up right away rather than sleeping for a day
+		let err = tokio::time::timeout(Duration::from_secs(5), client.counts())
+			.await
+			.expect("slept on a day-long Retry-After")
+			.unwrap_err();
+		assert!(matches!(err, Error::Tumblr(meta) if meta.status == 429));
+	}
+
+	#[tokio::test]
+	async fn test_edit_post_tags_keeps_state() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts/1234"))
+			.respond_with(ok_response(
+				r#"{
+	"id": 1234,
+	"blog_name": "staff",
+	"timestamp": 1654041600,
+	"state": "queued",
+	"scheduled_publish_time": 1709208000,
+	"tags": ["dogs"],
+	"content": [{ "type": "text", "text": "leap day corgi" }]
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		Mock::given(method("PUT"))
+			.and(path("/blog/staff/posts/1234"))
+			.and(body_json(json!({
+				"content": [{ "type": "text", "text": "leap day corgi" }],
+				"state": "queue",
+				"publish_on": "2024-02-29T12:00:00Z",
+				"tags": "corgi",
+			})))
+			.respond_with(ok_response(r#"{ "id": "1234" }"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		mock_client(&server)
+			.edit_post_tags("staff", 1234, vec![Tag::parse("corgi")])
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_edit_post_tags_keeps_labels() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/blog/staff/posts/1234"))
+			.respond_with(ok_response(
+				r#"{
+	"id": 1234,
+	"blog_name": "staff",
+	"timestamp": 1654041600,
+	"state": "published",
+	"tags": ["film"],
+	"community_labels": ["violence", "gore"],
+	"content": [{ "type": "text", "text": "the fight scene, annotated" }]
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		Mock::given(method("PUT"))
+			.and(path("/blog/staff/posts/1234"))
+			.and(body_json(json!({
+				"content": [{ "type": "text", "text": "the fight scene, annotated" }],
+				"state": "published",
+				"tags": "film,action",
+				"community_labels": ["violence", "gore"],
+			})))
+			.respond_with(ok_response(r#"{ "id": "1234" }"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		mock_client(&server)
+			.edit_post_tags(
+				"staff",
+				1234,
+				vec![Tag::parse("film"), Tag::parse("action")],
+			)
+			.await
+			.unwrap();
 	}
 }
@@ -0,0 +1,23 @@
+use std::fmt;
+
+use reqwest::{Method, Url};
+
+/// Hooks the client calls around every HTTP request, for wiring up counters without depending on a metrics library.
+/// Every method defaults to doing nothing, see [`ClientBuilder::metrics`](crate::ClientBuilder::metrics)
+pub trait Metrics: fmt::Debug + Send + Sync {
+	/// called just before a request is sent
+	fn on_request(&self, _method: &Method, _url: &Url) {}
+
+	/// called with the HTTP status of every response, including errors.
+	/// Not called if the request failed before getting a response
+	fn on_response(&self, _status: u16) {}
+
+	/// called when the API responds with `429 Too Many Requests`, after [`Metrics::on_response`]
+	fn on_rate_limited(&self) {}
+}
+
+/// The default [`Metrics`], which ignores everything
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {}
@@ -1,20 +1,57 @@
-use std::{cmp::Ordering, time::SystemTime};
+use std::{
+	cmp::Ordering,
+	fmt,
+	ops::{Deref, DerefMut},
+	str::FromStr,
+	time::{Duration, SystemTime},
+};
 
 use hmac::{Hmac, Mac};
 use rand::Rng;
-use reqwest::Url;
-use serde::Deserialize;
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
 use sha1::Sha1;
 
 use crate::{client::State, Authenticated, Client, Temporary, Unauthenticated};
 
-#[derive(Deserialize, Debug)]
+/// Serializable so the temporary credentials can be kept between the authorize and verify steps,
+/// see [`Client::resume_verification`].  The serialized form includes the secret, so store it accordingly
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct OAuthCredentials {
 	pub oauth_token: String,
 	pub oauth_token_secret: String,
 }
 
-fn oauth_encode(str: &str) -> String {
+impl OAuthCredentials {
+	#[inline]
+	pub fn new(oauth_token: String, oauth_token_secret: String) -> Self {
+		Self {
+			oauth_token,
+			oauth_token_secret,
+		}
+	}
+}
+
+/// Parses the `oauth_token=...&oauth_token_secret=...` form Tumblr returns from the token endpoints
+impl FromStr for OAuthCredentials {
+	type Err = crate::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		serde_urlencoded::from_str(s).map_err(crate::Error::DeserializeForm)
+	}
+}
+
+impl fmt::Debug for OAuthCredentials {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("OAuthCredentials")
+			.field("oauth_token", &"***")
+			.field("oauth_token_secret", &"***")
+			.finish()
+	}
+}
+
+/// Percent-encodes everything but the RFC 3986 unreserved characters
+pub(crate) fn oauth_encode(str: &str) -> String {
 	let mut out = String::new();
 
 	for ch in str.chars() {
@@ -24,8 +61,8 @@ fn oauth_encode(str: &str) -> String {
 				let mut buf = [0; 4];
 				let len = other.encode_utf8(&mut buf).len();
 
-				for i in 0..len {
-					out.push_str(&format!("%{:X}", buf[i]));
+				for byte in &buf[..len] {
+					out.push_str(&format!("%{:X}", byte));
 				}
 			}
 		}
@@ -46,6 +83,64 @@ fn generate_nonce() -> String {
 	base64::encode(buf)
 }
 
+/// Whether `req` has a `application/x-www-form-urlencoded` body, whose parameters are signed
+fn is_form(req: &reqwest::Request) -> bool {
+	req.headers()
+		.get(reqwest::header::CONTENT_TYPE)
+		.and_then(|content_type| content_type.to_str().ok())
+		.and_then(|content_type| content_type.split(';').next())
+		.is_some_and(|mime_type| {
+			mime_type
+				.trim()
+				.eq_ignore_ascii_case("application/x-www-form-urlencoded")
+		})
+}
+
+/// The string the OAuth 1.0a signature is computed over: the method, the URL without its query,
+/// and the sorted query, form body, and `oauth_params` parameters.
+/// JSON bodies aren't part of the signature at all
+fn signature_base_string(req: &reqwest::Request, oauth_params: Vec<(String, String)>) -> String {
+	let method = req.method().to_string().to_uppercase();
+
+	let mut url = req.url().clone();
+	let mut params: Vec<_> = url
+		.query_pairs()
+		.map(|(k, v)| (oauth_encode(&k), oauth_encode(&v)))
+		.collect();
+	url.set_query(None);
+
+	if let Some(body) = req
+		.body()
+		.and_then(reqwest::Body::as_bytes)
+		.filter(|_| is_form(req))
+	{
+		params.extend(
+			url::form_urlencoded::parse(body).map(|(k, v)| (oauth_encode(&k), oauth_encode(&v))),
+		);
+	}
+
+	params.extend(oauth_params);
+
+	params.sort_by(|(a, b), (c, d)| match a.cmp(c) {
+		Ordering::Equal => b.cmp(d),
+		other => other,
+	});
+
+	let params = params
+		.into_iter()
+		.map(|(k, v)| format!("{k}={v}"))
+		.collect::<Vec<_>>()
+		.join("&");
+
+	format!(
+		"{}&{}&{}",
+		oauth_encode(&method),
+		// `Url` already lowercases the scheme and host, and the path is signed as sent
+		oauth_encode(url.as_str()),
+		oauth_encode(&params),
+	)
+}
+
 pub(crate) struct Request<S: State> {
 	inner: reqwest::Request,
 	client: Client<S>,
@@ -65,43 +160,79 @@ impl<S: State> AsMut<reqwest::Request> for Request<S> {
 	}
 }
 
+impl<S: State> Deref for Request<S> {
+	type Target = reqwest::Request;
+
+	#[inline]
+	fn deref(&self) -> &Self::Target {
+		&self.inner
+	}
+}
+
+impl<S: State> DerefMut for Request<S> {
+	#[inline]
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.inner
+	}
+}
+
 impl<S: State> Request<S> {
+	#[inline]
+	pub(crate) fn new(client: &Client<S>, inner: reqwest::Request) -> Self {
+		Self {
+			inner,
+			client: client.clone(),
+		}
+	}
+
+	#[cfg(test)]
+	pub(crate) fn into_inner(self) -> reqwest::Request {
+		self.inner
+	}
+
 	pub(crate) fn sign(
 		self,
 		oauth_token: Option<&str>,
 		oauth_token_secret: Option<&str>,
 		other_params: Option<Vec<(&str, &str)>>,
 	) -> crate::Result<Self> {
-		let method = self.method().to_string().to_uppercase();
-
-		let mut url = self.url().clone();
-		let mut params: Vec<_> = url
-			.query_pairs()
-			.map(|(k, v)| (oauth_encode(&k), oauth_encode(&v)))
-			.collect();
-		url.set_query(None);
-		let base_uri = url.as_str().to_lowercase();
-
 		let timestamp = SystemTime::now()
 			.duration_since(SystemTime::UNIX_EPOCH)
 			.expect("it is before 1/1/1970")
 			.as_secs()
 			.to_string();
 
-		let nonce = generate_nonce();
+		self.sign_at(
+			oauth_token,
+			oauth_token_secret,
+			other_params,
+			&timestamp,
+			&generate_nonce(),
+		)
+	}
 
-		let oauth_params: Vec<_> = vec![
+	/// Like [`Request::sign`], but with the given `timestamp` and `nonce` so the signature is reproducible
+	pub(crate) fn sign_at(
+		mut self,
+		oauth_token: Option<&str>,
+		oauth_token_secret: Option<&str>,
+		other_params: Option<Vec<(&str, &str)>>,
+		timestamp: &str,
+		nonce: &str,
+	) -> crate::Result<Self> {
+		// two-legged requests (and the request token step) don't send `oauth_token` at all
+		let oauth_params: Vec<_> = [
 			(
 				"oauth_consumer_key",
 				self.client.oauth_consumer_key.as_str(),
 			),
-			("oauth_token", oauth_token.unwrap_or("")),
 			("oauth_signature_method", "HMAC-SHA1"),
-			("oauth_timestamp", &timestamp),
-			("oauth_nonce", &nonce),
+			("oauth_timestamp", timestamp),
+			("oauth_nonce", nonce),
 			("oauth_version", "1.0"),
 		]
 		.into_iter()
+		.chain(oauth_token.map(|oauth_token| ("oauth_token", oauth_token)))
 		.chain(other_params.unwrap_or_default())
 		.map(|(k, v)| (oauth_encode(k), oauth_encode(v)))
 		.collect();
@@ -111,29 +242,7 @@ impl<S: State> Request<S> {
 			.map(|(k, v)| format!(r#"{k}="{v}""#))
 			.collect();
 
-		params.extend(
-			oauth_params
-				.into_iter()
-				.map(|(k, v)| (k.to_owned(), v.to_owned())),
-		);
-
-		params.sort_by(|(a, b), (c, d)| match a.cmp(c) {
-			Ordering::Equal => b.cmp(d),
-			other => other,
-		});
-
-		let params = params
-			.into_iter()
-			.map(|(k, v)| format!("{k}={v}"))
-			.collect::<Vec<_>>()
-			.join("&");
-
-		let base_string = format!(
-			"{}&{}&{}",
-			oauth_encode(&method),
-			oauth_encode(&base_uri),
-			oauth_encode(&params),
-		);
+		let base_string = signature_base_string(&self.inner, oauth_params);
 
 		let secret = format!(
 			"{}&{}",
@@ -166,65 +275,129 @@ impl<S: State> Request<S> {
 		oauth_token_secret: Option<&str>,
 		other_params: Option<Vec<(&str, &str)>>,
 	) -> crate::Result<reqwest::Response> {
-		self.client
-			.client
-			.execute(
-				self.sign(oauth_token, oauth_token_secret, other_params)?
-					.inner,
-			)
-			.await
-			.map_err(|err| crate::Error::Http(err))
+		let Self { inner, client } = self.sign(oauth_token, oauth_token_secret, other_params)?;
+
+		client.execute(inner).await
 	}
 }
 
-pub(crate) trait SignRequest {
-	fn sign(
-		self,
-		oauth_consumer_key: &str,
-		oauth_client_secret: &str,
+/// The unsigned parts of a request, so it can be signed again with a fresh nonce and timestamp for each attempt
+pub(crate) struct ResignableRequest<S: State> {
+	client: Client<S>,
+	method: reqwest::Method,
+	url: Url,
+	headers: reqwest::header::HeaderMap,
+	body: Option<Vec<u8>>,
+	timeout: Option<Duration>,
+}
+
+impl<S: State> ResignableRequest<S> {
+	/// Keeps a copy of `req`, which must not be signed yet or have a streaming body
+	pub(crate) fn new(client: &Client<S>, req: reqwest::Request) -> Self {
+		Self {
+			client: client.clone(),
+			body: req
+				.body()
+				.and_then(reqwest::Body::as_bytes)
+				.map(<[u8]>::to_vec),
+			method: req.method().clone(),
+			url: req.url().clone(),
+			headers: req.headers().clone(),
+			timeout: req.timeout().copied(),
+		}
+	}
+
+	/// A newly signed copy of the request
+	pub(crate) fn sign(
+		&self,
+		oauth_token: Option<&str>,
+		oauth_token_secret: Option<&str>,
+		other_params: Option<Vec<(&str, &str)>>,
+	) -> crate::Result<reqwest::Request> {
+		let mut inner = reqwest::Request::new(self.method.clone(), self.url.clone());
+		*inner.headers_mut() = self.headers.clone();
+		*inner.body_mut() = self.body.clone().map(reqwest::Body::from);
+		*inner.timeout_mut() = self.timeout;
+
+		Ok(Request::new(&self.client, inner)
+			.sign(oauth_token, oauth_token_secret, other_params)?
+			.inner)
+	}
+
+	pub(crate) async fn sign_and_send(
+		&self,
 		oauth_token: Option<&str>,
 		oauth_token_secret: Option<&str>,
-		other_params: Vec<(&str, &str)>,
-	) -> Self;
+		other_params: Option<Vec<(&str, &str)>>,
+	) -> crate::Result<reqwest::Response> {
+		let retry = self.client.retry;
+
+		let mut attempt = 0;
+
+		loop {
+			// a fresh nonce and timestamp each time, or the retry is rejected as a replay
+			let req = self.sign(oauth_token, oauth_token_secret, other_params.clone())?;
+			let res = self.client.execute(req).await?;
+
+			if res.status() != StatusCode::TOO_MANY_REQUESTS || attempt >= retry.max_retries {
+				return Ok(res);
+			}
+
+			match retry.delay(attempt, res.headers()) {
+				Some(delay) => tokio::time::sleep(delay).await,
+				None => return Ok(res),
+			}
+			attempt += 1;
+		}
+	}
 }
 
 type HmacSha1 = Hmac<Sha1>;
 
-impl SignRequest for Request {
-	fn sign(
-		mut self,
-		oauth_consumer_key: &str,
-		oauth_client_secret: &str,
-		oauth_token: Option<&str>,
-		oauth_token_secret: Option<&str>,
-		other_params: Vec<(&str, &str)>,
-	) -> Self {
-		self
+/// The body of a token endpoint response, or [`Error::TokenExchangeFailed`](crate::Error::TokenExchangeFailed) if it has an error status,
+/// since error bodies aren't form data
+async fn check_token_response(res: reqwest::Response) -> crate::Result<String> {
+	let status = res.status();
+	let body = res.text().await?;
+
+	if status.is_success() {
+		Ok(body)
+	} else {
+		Err(crate::Error::TokenExchangeFailed {
+			status: status.as_u16(),
+			body,
+		})
 	}
 }
 
 impl Client<Unauthenticated> {
-	async fn create_temporary_credentials(self) -> crate::Result<OAuthCredentials> {
-		let res = self
+	async fn create_temporary_credentials(&self) -> crate::Result<OAuthCredentials> {
+		let req = self
 			.client
-			.execute(
-				self.client
-					.post("https://www.tumblr.com/oauth/request_token")
-					.build()?
-					.sign(
-						&self.oauth_consumer_key,
-						&self.oauth_client_secret,
-						None,
-						None,
-						// vec![("oauth_callback", "oob")],
-						vec![],
-					),
-			)
+			.post(format!("{}/request_token", self.oauth_base))
+			.build()?;
+
+		let res = Request::new(self, req)
+			// Some(vec![("oauth_callback", "oob")]),
+			.sign_and_send(None, None, None)
 			.await?;
 
-		let text = res.text().await?;
+		check_token_response(res).await?.parse()
+	}
 
-		serde_urlencoded::from_str(&text).map_err(crate::Error::DeserializeForm)
+	/// Exchanges temporary credentials saved from a [`Client<Temporary>`] (see [`Client::temporary_credentials`]) and the `oauth_verifier`
+	/// for the user's credentials, as [`Client::verify_token`] does.
+	/// For when the authorize URL and the redirect back are handled by different processes, e.g. separate requests to a web app
+	pub async fn resume_verification(
+		self,
+		temporary_credentials: OAuthCredentials,
+		oauth_verifier: String,
+	) -> crate::Result<Result<Client<Authenticated>, (Self, OAuthCredentials)>> {
+		let credentials = self
+			.exchange_verifier(&temporary_credentials, &oauth_verifier)
+			.await?;
+
+		Ok(self.with_credentials(credentials))
 	}
 
 	pub async fn try_into_temporary(
@@ -238,48 +411,463 @@ impl Client<Unauthenticated> {
 	}
 }
 
+impl<S: State> Client<S> {
+	/// Trades `temporary_credentials` and the user's `oauth_verifier` for the user's credentials
+	async fn exchange_verifier(
+		&self,
+		temporary_credentials: &OAuthCredentials,
+		oauth_verifier: &str,
+	) -> crate::Result<OAuthCredentials> {
+		let req = self
+			.client
+			.post(format!("{}/access_token", self.oauth_base))
+			.build()?;
+
+		let res = Request::new(self, req)
+			.sign_and_send(
+				Some(&temporary_credentials.oauth_token),
+				Some(&temporary_credentials.oauth_token_secret),
+				Some(vec![("oauth_verifier", oauth_verifier)]),
+			)
+			.await?;
+
+		check_token_response(res).await?.parse()
+	}
+}
+
 impl Client<Temporary> {
-	fn generate_callback_url(&self) -> String {
+	/// The temporary credentials, to save (e.g. in the user's session) and pass to [`Client::resume_verification`] later
+	#[inline]
+	pub fn temporary_credentials(&self) -> &OAuthCredentials {
+		&self.state().0
+	}
+
+	pub fn generate_callback_url(&self) -> String {
 		format!(
-			"https://www.tumblr.com/oauth/authorize?oauth_token={}",
+			"{}/authorize?oauth_token={}",
+			self.oauth_base,
 			&self.state().0.oauth_token
 		)
 	}
 
-	fn parse_redirect_url(url: &str) -> Option<String> {
-		let parsed: Url = url.parse().ok()?;
+	/// Extracts the `oauth_verifier` from the URL Tumblr redirected the user to, looking in both the query and the fragment.
+	/// Returns [`Error::AuthDenied`](crate::Error::AuthDenied) if the user declined to authorize the application
+	pub fn parse_redirect_url(url: &str) -> crate::Result<Option<String>> {
+		let parsed: Url = url.parse()?;
 
-		parsed
-			.query_pairs()
-			.find(|(k, _)| k == "oauth_verifier")
-			.map(|(_, v)| v.into_owned())
+		let fragment_pairs =
+			url::form_urlencoded::parse(parsed.fragment().unwrap_or("").as_bytes());
+		let pairs = parsed.query_pairs().chain(fragment_pairs);
+
+		let mut verifier = None;
+
+		for (k, v) in pairs {
+			match &*k {
+				"denied" => return Err(crate::Error::AuthDenied),
+				"oauth_verifier" if verifier.is_none() => verifier = Some(v.into_owned()),
+				_ => (),
+			}
+		}
+
+		Ok(verifier)
 	}
 
 	pub async fn verify_token(
 		self,
-		temporary_credentials: OAuthCredentials,
 		oauth_verifier: String,
 	) -> crate::Result<Result<Client<Authenticated>, (Self, OAuthCredentials)>> {
-		let res = self
-			.client
-			.execute(
-				self.client
-					.post("https://www.tumblr.com/oauth/access_token")
-					.build()?
-					.sign(
-						&self.oauth_consumer_key,
-						&self.oauth_client_secret,
-						Some(&temporary_credentials.oauth_token),
-						Some(&temporary_credentials.oauth_token_secret),
-						vec![("oauth_verifier", &oauth_verifier)],
-					),
-			)
+		let credentials = self
+			.exchange_verifier(&self.state().0, &oauth_verifier)
 			.await?;
 
-		let credentials = serde_urlencoded::from_str(&res.text().await?)?;
-
 		Ok(self
 			.try_into_other_state(Authenticated(credentials))
 			.map_err(|(client, state)| (client, state.0)))
 	}
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	use super::*;
+	use crate::client::tests::mock_builder;
+
+	/// Recomputes the signature of a request sent to `server` by a client from [`authenticated`](crate::client::tests::authenticated),
+	/// including any form parameters, and checks it matches the one in the `Authorization` header
+	pub(crate) fn assert_valid_signature(server: &MockServer, request: &wiremock::Request) {
+		// the mock server splits header values on commas, which the `Authorization` header is full of
+		let header = |name: &str| {
+			request
+				.headers
+				.iter()
+				.find(|(k, _)| k.as_str() == name)
+				.map(|(_, v)| {
+					v.iter()
+						.map(|value| value.as_str())
+						.collect::<Vec<_>>()
+						.join(",")
+				})
+		};
+
+		let authorization = header("authorization").unwrap();
+
+		let mut params: Vec<_> = request
+			.url
+			.query_pairs()
+			.map(|(k, v)| (oauth_encode(&k), oauth_encode(&v)))
+			.collect();
+
+		if header("content-type").as_deref() == Some("application/x-www-form-urlencoded") {
+			params.extend(
+				url::form_urlencoded::parse(&request.body)
+					.map(|(k, v)| (oauth_encode(&k), oauth_encode(&v))),
+			);
+		}
+
+		let mut signature = None;
+		for param in authorization.strip_prefix("OAuth ").unwrap().split(',') {
+			let (k, v) = param.split_once('=').unwrap();
+			let v = v.trim_matches('"').to_owned();
+
+			match k {
+				"oauth_signature" => signature = Some(v),
+				_ => params.push((k.to_owned(), v)),
+			}
+		}
+		params.sort();
+
+		// the mock server reports its host as `localhost`, not the address the client signed
+		let url = format!("{}{}", server.uri(), request.url.path());
+
+		let base_string = format!(
+			"{}&{}&{}",
+			request.method,
+			oauth_encode(&url),
+			oauth_encode(
+				&params
+					.iter()
+					.map(|(k, v)| format!("{k}={v}"))
+					.collect::<Vec<_>>()
+					.join("&")
+			),
+		);
+
+		let mut hmac = HmacSha1::new_from_slice(b"client-secret&token-secret").unwrap();
+		hmac.update(base_string.as_bytes());
+
+		assert_eq!(
+			signature,
+			Some(oauth_encode(&base64::encode(hmac.finalize().into_bytes()))),
+			"signature doesn't match base string {base_string}"
+		);
+	}
+
+	#[tokio::test]
+	async fn test_temporary_credentials_error_body() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("POST"))
+			.and(path("/oauth/request_token"))
+			.respond_with(
+				ResponseTemplate::new(401)
+					.set_body_string("oauth_signature [abc] does not match expected value [def]"),
+			)
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let err = mock_builder(&server)
+			.build()
+			.try_into_temporary()
+			.await
+			.unwrap_err();
+
+		assert!(matches!(
+			err,
+			crate::Error::TokenExchangeFailed { status: 401, body } if body.contains("does not match")
+		));
+	}
+
+	#[test]
+	fn test_two_legged_signature() {
+		let client = Client::new("consumer-key".to_owned(), "client-secret".to_owned());
+		let req = client
+			.client
+			.get("https://api.tumblr.com/v2/blog/staff/posts?tag=cats")
+			.build()
+			.unwrap();
+
+		let req = ResignableRequest::new(&client, req)
+			.sign(None, None, None)
+			.unwrap();
+
+		let authorization = req.headers()[reqwest::header::AUTHORIZATION]
+			.to_str()
+			.unwrap()
+			.strip_prefix("OAuth ")
+			.unwrap();
+
+		let mut params = vec![("tag".to_owned(), "cats".to_owned())];
+		let mut signature = None;
+		for param in authorization.split(',') {
+			let (k, v) = param.split_once('=').unwrap();
+			let v = v.trim_matches('"').to_owned();
+
+			match k {
+				"oauth_signature" => signature = Some(v),
+				"oauth_token" => panic!("two-legged request sent oauth_token"),
+				_ => params.push((k.to_owned(), v)),
+			}
+		}
+		params.sort();
+
+		let base_string = format!(
+			"GET&{}&{}",
+			oauth_encode("https://api.tumblr.com/v2/blog/staff/posts"),
+			oauth_encode(
+				&params
+					.iter()
+					.map(|(k, v)| format!("{k}={v}"))
+					.collect::<Vec<_>>()
+					.join("&")
+			),
+		);
+
+		let mut hmac = HmacSha1::new_from_slice(b"client-secret&").unwrap();
+		hmac.update(base_string.as_bytes());
+
+		assert_eq!(
+			signature,
+			Some(oauth_encode(&base64::encode(hmac.finalize().into_bytes())))
+		);
+	}
+
+	#[test]
+	fn test_form_body_in_base_string() {
+		let client = reqwest::Client::new();
+		let oauth_params = || vec![("oauth_nonce".to_owned(), "abc".to_owned())];
+
+		let req = client
+			.post("https://api.tumblr.com/v2/blog/staff/post/delete?x=1")
+			.header(
+				reqwest::header::CONTENT_TYPE,
+				"Application/X-WWW-Form-Urlencoded; charset=UTF-8",
+			)
+			.body("id=1234&note=a%20b")
+			.build()
+			.unwrap();
+
+		assert_eq!(
+			signature_base_string(&req, oauth_params()),
+			format!(
+				"POST&{}&{}",
+				oauth_encode("https://api.tumblr.com/v2/blog/staff/post/delete"),
+				oauth_encode("id=1234&note=a%20b&oauth_nonce=abc&x=1"),
+			)
+		);
+
+		let req = client
+			.post("https://api.tumblr.com/v2/blog/staff/posts")
+			.json(&serde_json::json!({ "id": 1234 }))
+			.build()
+			.unwrap();
+
+		assert_eq!(
+			signature_base_string(&req, oauth_params()),
+			format!(
+				"POST&{}&{}",
+				oauth_encode("https://api.tumblr.com/v2/blog/staff/posts"),
+				oauth_encode("oauth_nonce=abc"),
+			)
+		);
+	}
+
+	#[test]
+	fn test_signature_keeps_path_case() {
+		let client = crate::client::tests::authenticated(Client::builder(
+			"consumer-key".to_owned(),
+			"client-secret".to_owned(),
+		));
+		let req = client
+			.client
+			.get("https://API.Tumblr.com/v2/blog/t:0aY0xL2Fi1OFJg4YxpmegQ/posts?tag=Corgi")
+			.build()
+			.unwrap();
+
+		let req = Request::new(&client, req)
+			.sign_at(
+				Some("token"),
+				Some("token-secret"),
+				None,
+				"1654041600",
+				"bm9uY2U=",
+			)
+			.unwrap()
+			.into_inner();
+
+		// computed independently over
+		// GET&https%3A%2F%2Fapi.tumblr.com%2Fv2%2Fblog%2Ft%3A0aY0xL2Fi1OFJg4YxpmegQ%2Fposts&...%26tag%3DCorgi
+		assert_eq!(
+			req.headers()[reqwest::header::AUTHORIZATION]
+				.to_str()
+				.unwrap()
+				.split(',')
+				.find_map(|param| param.strip_prefix("oauth_signature=")),
+			Some(r#""Ax1Y%2BZf1u0wIVGz6yP9XNzpF3Tg%3D""#)
+		);
+	}
+
+	#[tokio::test]
+	async fn test_resume_verification() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("POST"))
+			.and(path("/oauth/request_token"))
+			.respond_with(
+				ResponseTemplate::new(200)
+					.set_body_string("oauth_token=temporary&oauth_token_secret=temporary-secret"),
+			)
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		Mock::given(method("POST"))
+			.and(path("/oauth/access_token"))
+			.respond_with(
+				ResponseTemplate::new(200)
+					.set_body_string("oauth_token=token&oauth_token_secret=token-secret"),
+			)
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let saved = {
+			let temporary = mock_builder(&server)
+				.build()
+				.try_into_temporary()
+				.await
+				.unwrap()
+				.unwrap();
+
+			serde_json::to_string(temporary.temporary_credentials()).unwrap()
+		};
+
+		let client = mock_builder(&server)
+			.build()
+			.resume_verification(serde_json::from_str(&saved).unwrap(), "verifier".to_owned())
+			.await
+			.unwrap()
+			.unwrap();
+
+		assert_eq!(client.state().0.oauth_token, "token");
+		assert_eq!(client.state().0.oauth_token_secret, "token-secret");
+
+		let requests = server.received_requests().await.unwrap();
+		let authorization = requests[1]
+			.headers
+			.iter()
+			.find(|(name, _)| name.as_str() == "authorization")
+			.map(|(_, values)| {
+				values
+					.iter()
+					.map(|value| value.as_str())
+					.collect::<Vec<_>>()
+			})
+			.unwrap();
+
+		assert!(authorization.contains(&r#"oauth_token="temporary""#));
+		assert!(authorization.contains(&r#"oauth_verifier="verifier""#));
+	}
+
+	#[test]
+	fn test_parse_credentials() {
+		let credentials: OAuthCredentials = "oauth_token=abc&oauth_token_secret=x%2Fy%3D"
+			.parse()
+			.unwrap();
+
+		assert_eq!(credentials.oauth_token, "abc");
+		assert_eq!(credentials.oauth_token_secret, "x/y=");
+
+		let credentials = OAuthCredentials::new("abc".to_owned(), "def".to_owned());
+		assert_eq!(credentials.oauth_token, "abc");
+		assert_eq!(credentials.oauth_token_secret, "def");
+
+		assert!(matches!(
+			"oauth_token=abc".parse::<OAuthCredentials>(),
+			Err(crate::Error::DeserializeForm(_))
+		));
+	}
+
+	#[test]
+	fn test_resign_uses_fresh_nonce() {
+		let client = Client::new("consumer-key".to_owned(), "client-secret".to_owned());
+		let req = client
+			.client
+			.post("https://api.tumblr.com/v2/blog/staff/posts")
+			.body("{}")
+			.build()
+			.unwrap();
+
+		let req = ResignableRequest::new(&client, req);
+
+		let nonce = |req: &reqwest::Request| {
+			req.headers()[reqwest::header::AUTHORIZATION]
+				.to_str()
+				.unwrap()
+				.split(',')
+				.find_map(|param| param.strip_prefix("oauth_nonce="))
+				.unwrap()
+				.to_owned()
+		};
+
+		let first = req.sign(Some("token"), Some("secret"), None).unwrap();
+		let second = req.sign(Some("token"), Some("secret"), None).unwrap();
+
+		assert_ne!(nonce(&first), nonce(&second));
+		assert_eq!(
+			first
+				.headers()
+				.get_all(reqwest::header::AUTHORIZATION)
+				.iter()
+				.count(),
+			1
+		);
+		assert_eq!(
+			first.body().and_then(reqwest::Body::as_bytes),
+			second.body().and_then(reqwest::Body::as_bytes)
+		);
+	}
+
+	#[test]
+	fn test_parse_redirect_url() {
+		assert_eq!(
+			Client::<Temporary>::parse_redirect_url(
+				"https://example.com/callback?oauth_token=abc&oauth_verifier=xyz"
+			)
+			.unwrap(),
+			Some("xyz".to_owned())
+		);
+
+		assert_eq!(
+			Client::<Temporary>::parse_redirect_url(
+				"https://example.com/callback#oauth_token=abc&oauth_verifier=xyz"
+			)
+			.unwrap(),
+			Some("xyz".to_owned())
+		);
+
+		assert_eq!(
+			Client::<Temporary>::parse_redirect_url("https://example.com/callback").unwrap(),
+			None
+		);
+
+		assert!(matches!(
+			Client::<Temporary>::parse_redirect_url("https://example.com/callback?denied=abc"),
+			Err(crate::Error::AuthDenied)
+		));
+	}
+}
@@ -0,0 +1,204 @@
+//! Filling in a [`ContentBlock::Link`] from the Open Graph `<meta>` tags of the page it links to
+
+use std::{cell::RefCell, collections::HashMap};
+
+use html5ever::{
+	tendril::StrTendril,
+	tokenizer::{
+		states::RawKind, BufferQueue, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer,
+		TokenizerOpts,
+	},
+};
+use reqwest::Url;
+
+use crate::{
+	client::State,
+	post::{parse_link_url, ContentBlock, Media},
+	Client,
+};
+
+/// Collects the `og:` properties of `<meta>` tags, keeping the first value of each
+#[derive(Default)]
+struct OpenGraphSink {
+	properties: RefCell<HashMap<String, String>>,
+}
+
+impl TokenSink for OpenGraphSink {
+	type Handle = ();
+
+	fn process_token(&self, token: Token, _line_number: u64) -> TokenSinkResult<()> {
+		let tag = match token {
+			Token::TagToken(tag) if tag.kind == TagKind::StartTag => tag,
+			_ => return TokenSinkResult::Continue,
+		};
+
+		// without a tree builder, the tokenizer has to be told which elements contain raw text,
+		// so a `<meta>` inside a script's string isn't picked up
+		match &*tag.name {
+			"script" => return TokenSinkResult::RawData(RawKind::ScriptData),
+			"style" | "noscript" => return TokenSinkResult::RawData(RawKind::Rawtext),
+			"title" | "textarea" => return TokenSinkResult::RawData(RawKind::Rcdata),
+			"meta" => (),
+			_ => return TokenSinkResult::Continue,
+		}
+
+		let attr = |name: &str| {
+			tag.attrs
+				.iter()
+				.find(|attr| &*attr.name.local == name)
+				.map(|attr| attr.value.to_string())
+		};
+
+		// some sites use `name` instead of the `property` the spec asks for
+		let property = attr("property").or_else(|| attr("name"));
+
+		if let (Some(property), Some(content)) = (property, attr("content")) {
+			if property.starts_with("og:") && !content.trim().is_empty() {
+				self.properties
+					.borrow_mut()
+					.entry(property)
+					.or_insert_with(|| content.trim().to_owned());
+			}
+		}
+
+		TokenSinkResult::Continue
+	}
+}
+
+/// The `og:` properties in `html`, e.g. `og:title`
+fn parse_properties(html: &str) -> HashMap<String, String> {
+	let tokenizer = Tokenizer::new(OpenGraphSink::default(), TokenizerOpts::default());
+
+	let input = BufferQueue::default();
+	input.push_back(StrTendril::from_slice(html));
+
+	let _ = tokenizer.feed(&input);
+	tokenizer.end();
+
+	tokenizer.sink.properties.into_inner()
+}
+
+/// A [`ContentBlock::Link`] to `url`, with whichever of `og:title`, `og:description`, `og:site_name` and `og:image` the page has.
+/// A relative `og:image` is resolved against `url`
+fn link_block(url: &Url, mut properties: HashMap<String, String>) -> ContentBlock {
+	let poster = properties
+		.remove("og:image")
+		.and_then(|image| url.join(&image).ok())
+		.map(|image| Media {
+			url: image.into(),
+			mime_type: properties.remove("og:image:type"),
+			width: properties
+				.get("og:image:width")
+				.and_then(|width| width.parse().ok()),
+			height: properties
+				.get("og:image:height")
+				.and_then(|height| height.parse().ok()),
+			identifier: None,
+		});
+
+	ContentBlock::Link {
+		url: url.to_string(),
+		title: properties.remove("og:title"),
+		description: properties.remove("og:description"),
+		author: None,
+		site_name: properties.remove("og:site_name"),
+		display_url: None,
+		poster,
+	}
+}
+
+impl<S: State> Client<S> {
+	/// Fetches `url` and creates a [`ContentBlock::Link`] to it from the page's Open Graph tags.
+	/// Tags the page doesn't have are left as `None`.
+	/// Returns [`Error::Validation`](crate::Error::Validation) if `url` isn't `http` or `https`
+	pub async fn enrich_link(&self, url: &str) -> crate::Result<ContentBlock> {
+		let parsed = parse_link_url(url)?;
+
+		let res = self
+			.execute(self.client.get(parsed.clone()).build()?)
+			.await?
+			.error_for_status()?;
+
+		Ok(link_block(&parsed, parse_properties(&res.text().await?)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	use crate::client::tests::mock_client;
+
+	use super::*;
+
+	#[tokio::test]
+	async fn test_enrich_link() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/article"))
+			.respond_with(ResponseTemplate::new(200).set_body_raw(
+				r#"<!doctype html>
+<html>
+<head>
+	<title>Ignored</title>
+	<script>document.write('<meta property="og:title" content="Injected">')</script>
+	<meta property="og:title" content="A Corgi Walks Into a Bar">
+	<meta property="og:site_name" content="The Example Times" />
+	<meta name="og:description" content="  And orders a pupcake. ">
+	<meta property="og:image" content="/images/corgi.jpg">
+	<meta property="og:image:width" content="1200">
+	<meta property="og:image:height" content="630">
+	<meta property="og:title" content="Duplicate">
+</head>
+<body></body>
+</html>"#,
+				"text/html",
+			))
+			.mount(&server)
+			.await;
+
+		let url = format!("{}/article", server.uri());
+		let block = mock_client(&server).enrich_link(&url).await.unwrap();
+
+		assert_eq!(
+			block,
+			ContentBlock::Link {
+				url,
+				title: Some("A Corgi Walks Into a Bar".to_owned()),
+				description: Some("And orders a pupcake.".to_owned()),
+				author: None,
+				site_name: Some("The Example Times".to_owned()),
+				display_url: None,
+				poster: Some(Media {
+					url: format!("{}/images/corgi.jpg", server.uri()),
+					mime_type: None,
+					width: Some(1200),
+					height: Some(630),
+					identifier: None,
+				}),
+			}
+		);
+	}
+
+	#[test]
+	fn test_missing_tags() {
+		let url: Url = "https://example.com/".parse().unwrap();
+
+		assert_eq!(
+			link_block(&url, parse_properties("<p>no metadata here")),
+			ContentBlock::Link {
+				url: url.to_string(),
+				title: None,
+				description: None,
+				author: None,
+				site_name: None,
+				display_url: None,
+				poster: None,
+			}
+		);
+	}
+}
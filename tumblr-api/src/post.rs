@@ -1,11 +1,25 @@
+use std::{
+	cmp::Ordering,
+	collections::{BTreeMap, HashMap},
+	convert::Infallible,
+	fmt,
+	hash::{Hash, Hasher},
+	str::FromStr,
+};
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+use crate::{upload::MediaFile, Error};
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct BlogInfo {
-	uuid: String,
+	pub uuid: String,
+	/// sent on retrieve, not needed on create
+	pub name: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum TextSubtype {
 	/// intended for post headings
@@ -26,12 +40,54 @@ pub enum TextSubtype {
 	UnorderedListItem,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+impl TextSubtype {
+	/// [`TextSubtype::OrderedListItem`] or [`TextSubtype::UnorderedListItem`].
+	/// Consecutive list items form one list, nested by their `indent_level`
+	pub fn is_list_item(&self) -> bool {
+		matches!(self, Self::OrderedListItem | Self::UnorderedListItem)
+	}
+
+	/// [`TextSubtype::Heading1`] or [`TextSubtype::Heading2`]
+	pub fn is_heading(&self) -> bool {
+		matches!(self, Self::Heading1 | Self::Heading2)
+	}
+
+	/// The HTML element a block of this subtype renders as.  List items are `li`, see [`TextSubtype::list_tag`] for their parent
+	pub fn html_tag(&self) -> &'static str {
+		match self {
+			Self::Heading1 => "h1",
+			Self::Heading2 => "h2",
+			Self::Indented => "blockquote",
+			Self::OrderedListItem | Self::UnorderedListItem => "li",
+			Self::Quirky | Self::Quote | Self::Chat => "p",
+		}
+	}
+
+	/// The HTML list (`ol` or `ul`) wrapping list items, `None` for other subtypes
+	pub fn list_tag(&self) -> Option<&'static str> {
+		match self {
+			Self::OrderedListItem => Some("ol"),
+			Self::UnorderedListItem => Some("ul"),
+			_ => None,
+		}
+	}
+
+	/// The plaintext prefix for the list item at `index` (from 0) in its list, `None` for other subtypes
+	pub fn list_marker(&self, index: usize) -> Option<String> {
+		match self {
+			Self::OrderedListItem => Some(format!("{}.", index + 1)),
+			Self::UnorderedListItem => Some("•".to_owned()),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PostInfo {
-	id: u64,
+	pub id: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum TextFormatType {
 	Bold,
@@ -50,7 +106,38 @@ pub enum TextFormatType {
 	},
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+/// Parses `url`, returning [`Error::Validation`] unless it's an `http` or `https` URL
+pub(crate) fn parse_link_url(url: &str) -> crate::Result<reqwest::Url> {
+	let parsed: reqwest::Url = url
+		.parse()
+		.map_err(|_| Error::Validation(format!("invalid link URL {url:?}")))?;
+
+	match parsed.scheme() {
+		"http" | "https" => Ok(parsed),
+		scheme => Err(Error::Validation(format!(
+			"link URL {url:?} has disallowed scheme {scheme:?}"
+		))),
+	}
+}
+
+impl TextFormatType {
+	/// Creates a [`TextFormatType::Link`], rejecting URLs that aren't `http` or `https`
+	pub fn link(url: String) -> crate::Result<Self> {
+		parse_link_url(&url)?;
+
+		Ok(Self::Link { url })
+	}
+
+	/// The URL of a [`TextFormatType::Link`], `None` for every other format type
+	pub fn url(&self) -> Option<&str> {
+		match self {
+			Self::Link { url } => Some(url),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TextFormatting {
 	/// indexed by chars, not bytes
 	start: usize,
@@ -62,11 +149,43 @@ pub struct TextFormatting {
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct Media {
-	url: String,
+	/// empty for media uploaded with the post, which is referred to by `identifier` instead
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pub(crate) url: String,
 	#[serde(rename = "type")]
-	mime_type: Option<String>,
-	width: Option<u64>,
-	height: Option<u64>,
+	pub(crate) mime_type: Option<String>,
+	pub(crate) width: Option<u64>,
+	pub(crate) height: Option<u64>,
+	/// the name of the multipart part holding the media, see [`MediaFile`](crate::upload::MediaFile)
+	pub(crate) identifier: Option<String>,
+}
+
+impl Media {
+	/// An image or other media at `url`.  `width` and `height` are in pixels, and help clients lay out the post before it loads
+	pub fn new(url: String, width: Option<u64>, height: Option<u64>) -> Self {
+		Self {
+			url,
+			mime_type: None,
+			width,
+			height,
+			identifier: None,
+		}
+	}
+
+	/// A reference to `file`, which must be uploaded along with the post
+	pub fn uploaded(file: &MediaFile) -> Self {
+		Self {
+			url: String::new(),
+			mime_type: Some(file.mime_type().to_owned()),
+			width: None,
+			height: None,
+			identifier: Some(file.identifier().to_owned()),
+		}
+	}
+
+	pub fn url(&self) -> &str {
+		&self.url
+	}
 }
 
 #[serde_with::skip_serializing_none]
@@ -92,6 +211,62 @@ pub enum Attribution {
 	},
 }
 
+impl Attribution {
+	/// Creates an [`Attribution::App`].  If `app_name` is `None`, it defaults to `url`'s host without a leading `www.`
+	pub fn app(
+		url: String,
+		app_name: Option<String>,
+		display_text: Option<String>,
+		logo: Option<Media>,
+	) -> crate::Result<Self> {
+		let app_name = match app_name {
+			Some(app_name) => app_name,
+			None => {
+				let parsed: reqwest::Url = url
+					.parse()
+					.map_err(|_| Error::Validation(format!("invalid app URL {url:?}")))?;
+
+				let host = parsed
+					.host_str()
+					.ok_or_else(|| Error::Validation(format!("app URL {url:?} has no host")))?;
+
+				host.strip_prefix("www.").unwrap_or(host).to_owned()
+			}
+		};
+
+		Ok(Self::App {
+			url,
+			app_name: Some(app_name),
+			display_text,
+			logo,
+		})
+	}
+
+	/// The name of an [`Attribution::App`], `None` for other attributions or if it wasn't sent
+	pub fn app_name(&self) -> Option<&str> {
+		match self {
+			Self::App { app_name, .. } => app_name.as_deref(),
+			_ => None,
+		}
+	}
+
+	/// The display text of an [`Attribution::App`], `None` for other attributions or if it wasn't sent
+	pub fn display_text(&self) -> Option<&str> {
+		match self {
+			Self::App { display_text, .. } => display_text.as_deref(),
+			_ => None,
+		}
+	}
+
+	/// The logo of an [`Attribution::App`], `None` for other attributions or if it wasn't sent
+	pub fn logo(&self) -> Option<&Media> {
+		match self {
+			Self::App { logo, .. } => logo.as_ref(),
+			_ => None,
+		}
+	}
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(untagged)]
 pub enum MediaSource {
@@ -142,7 +317,7 @@ pub enum ContentBlock {
 		description: Option<String>,
 		author: Option<String>,
 		site_name: Option<String>,
-		/// ignored on create, sent on retrieve
+		/// sent on retrieve, dropped from the body on create
 		display_url: Option<String>,
 		poster: Option<Media>,
 	},
@@ -178,6 +353,167 @@ pub enum ContentBlock {
 	},
 }
 
+impl ContentBlock {
+	/// Creates a [`ContentBlock::Link`] with an optional `poster` image, e.g. from [`Media::new`].
+	/// Returns [`Error::Validation`] if `url` or the poster's URL isn't `http` or `https`
+	pub fn link(
+		url: String,
+		title: Option<String>,
+		description: Option<String>,
+		poster: Option<Media>,
+	) -> crate::Result<Self> {
+		parse_link_url(&url)?;
+
+		if let Some(poster) = &poster {
+			parse_link_url(&poster.url)?;
+		}
+
+		Ok(Self::Link {
+			url,
+			title,
+			description,
+			author: None,
+			site_name: None,
+			display_url: None,
+			poster,
+		})
+	}
+
+	/// A video (or, for `image/gif`, an image) block for `video`, shown as `poster` until it plays.
+	/// Both files must be uploaded with the post, see [`Client::create_post_with_media`](crate::Client::create_post_with_media).
+	/// Returns [`Error::Validation`] unless `video` is a video or GIF and `poster` is an image
+	pub fn uploaded_video(video: &MediaFile, poster: Option<&MediaFile>) -> crate::Result<Self> {
+		if let Some(poster) = poster {
+			if !poster.mime_type().starts_with("image/") {
+				return Err(Error::Validation(format!(
+					"poster {:?} must be an image, not {}",
+					poster.identifier(),
+					poster.mime_type()
+				)));
+			}
+		}
+
+		let poster = poster.map(Media::uploaded);
+
+		match video.mime_type() {
+			"image/gif" => Ok(Self::Image {
+				media: vec![Media::uploaded(video)],
+				feedback_token: None,
+				poster,
+				attribution: None,
+				alt_text: None,
+				caption: None,
+			}),
+			mime_type if mime_type.starts_with("video/") => Ok(Self::Video {
+				source: MediaSource::Media {
+					media: Media::uploaded(video),
+				},
+				embed_html: None,
+				embed_iframe: None,
+				embed_url: None,
+				poster,
+				attribution: None,
+				can_autoplay_on_cellular: None,
+			}),
+			mime_type => Err(Error::Validation(format!(
+				"{:?} must be a video or GIF, not {mime_type}",
+				video.identifier()
+			))),
+		}
+	}
+
+	/// Splits a [`ContentBlock::Text`] into blocks of at most `max_chars` chars each (a `max_chars` of 0 is treated as 1),
+	/// keeping its subtype and indent level, and cutting its `formatting` ranges to fit each piece.
+	/// Any other block is returned as is
+	pub fn split_text_block(self, max_chars: usize) -> Vec<Self> {
+		let Self::Text {
+			text,
+			subtype,
+			indent_level,
+			formatting,
+		} = self
+		else {
+			return vec![self];
+		};
+
+		let max_chars = max_chars.max(1);
+		let chars: Vec<char> = text.chars().collect();
+
+		if chars.len() <= max_chars {
+			return vec![Self::Text {
+				text,
+				subtype,
+				indent_level,
+				formatting,
+			}];
+		}
+
+		chars
+			.chunks(max_chars)
+			.enumerate()
+			.map(|(i, piece)| {
+				let start = i * max_chars;
+				let end = start + piece.len();
+
+				let formatting: Vec<_> = formatting
+					.iter()
+					.flatten()
+					.filter(|format| format.start < end && format.end > start)
+					.map(|format| TextFormatting {
+						start: format.start.max(start) - start,
+						end: format.end.min(end) - start,
+						format_type: format.format_type.clone(),
+					})
+					.collect();
+
+				Self::Text {
+					text: piece.iter().collect(),
+					subtype: subtype.clone(),
+					indent_level,
+					formatting: (!formatting.is_empty()).then_some(formatting),
+				}
+			})
+			.collect()
+	}
+
+	/// Compares only the fields the post's author controls, ignoring ones Tumblr fills in:
+	/// `display_url`, `feedback_token`, and the `width` and `height` of media.
+	/// For checking whether a fetched post still matches what was created
+	pub fn semantic_eq(&self, other: &Self) -> bool {
+		self.author_controlled() == other.author_controlled()
+	}
+
+	fn author_controlled(&self) -> serde_json::Value {
+		fn strip_dimensions(media: Option<&mut serde_json::Value>) {
+			match media {
+				Some(serde_json::Value::Array(media)) => {
+					media.iter_mut().for_each(|media| strip_dimensions(Some(media)))
+				}
+				Some(serde_json::Value::Object(media)) => {
+					media.remove("width");
+					media.remove("height");
+				}
+				_ => (),
+			}
+		}
+
+		let mut value = serde_json::to_value(self).expect("ContentBlock is always serializable");
+
+		if let serde_json::Value::Object(block) = &mut value {
+			block.remove("display_url");
+			block.remove("feedback_token");
+			strip_dimensions(block.get_mut("media"));
+			strip_dimensions(block.get_mut("poster"));
+
+			if let Some(serde_json::Value::Object(source)) = block.get_mut("source") {
+				strip_dimensions(source.get_mut("media"));
+			}
+		}
+
+		value
+	}
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum RowDisplayMode {
@@ -227,96 +563,955 @@ pub enum ReblogTrail {
 		content: Vec<ContentBlock>,
 		layout: Vec<LayoutBlock>,
 	},
+	/// a reference without the post's content, sent with [`TrailMode::Compact`]
+	Reference {
+		post: PostInfo,
+		blog: BlogInfo,
+	},
+}
+
+/// How much of each post in a reblog trail to send
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrailMode {
+	/// only the post and blog, as [`ReblogTrail::Reference`].  Cheaper for long reblog chains
+	Compact,
+	/// each post's content and layout inline.  Tumblr's default
+	Full,
+}
+
+/// Where a post is published
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PostState {
+	Published,
+	/// added to the blog's queue, see [`Blog::queue`](crate::blog::Blog::queue)
+	Queue,
+	Draft,
+	/// only visible to the blog's members
+	Private,
+}
+
+/// A content label for mature themes, see [`Post::community_labels`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum CommunityLabel {
+	DrugUse,
+	Violence,
+	SexualThemes,
+	/// a label this crate doesn't know yet, kept as sent
+	Other(String),
+}
+
+impl From<String> for CommunityLabel {
+	fn from(label: String) -> Self {
+		match label.as_str() {
+			"drug_use" => Self::DrugUse,
+			"violence" => Self::Violence,
+			"sexual_themes" => Self::SexualThemes,
+			_ => Self::Other(label),
+		}
+	}
+}
+
+impl From<CommunityLabel> for String {
+	fn from(label: CommunityLabel) -> Self {
+		match label {
+			CommunityLabel::DrugUse => "drug_use".to_owned(),
+			CommunityLabel::Violence => "violence".to_owned(),
+			CommunityLabel::SexualThemes => "sexual_themes".to_owned(),
+			CommunityLabel::Other(label) => label,
+		}
+	}
+}
+
+/// A post tag, without the leading `#`.
+/// Tumblr matches tags case-insensitively, so equality and hashing ignore case, but the tag is displayed as written
+#[derive(Debug, Clone, Eq)]
+pub struct Tag(String);
+
+impl Tag {
+	/// Trims whitespace and any leading `#`s, so `"#Foo"` and `"  foo "` are the same tag
+	pub fn parse(tag: &str) -> Self {
+		Self(tag.trim().trim_start_matches('#').trim_start().to_owned())
+	}
+
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+impl PartialEq for Tag {
+	fn eq(&self, other: &Self) -> bool {
+		self.0.to_lowercase() == other.0.to_lowercase()
+	}
+}
+
+impl Hash for Tag {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.0.to_lowercase().hash(state)
+	}
+}
+
+impl PartialOrd for Tag {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+/// Ignores case, like [`PartialEq`]
+impl Ord for Tag {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.0.to_lowercase().cmp(&other.0.to_lowercase())
+	}
+}
+
+impl fmt::Display for Tag {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+impl FromStr for Tag {
+	type Err = Infallible;
+
+	fn from_str(tag: &str) -> Result<Self, Self::Err> {
+		Ok(Self::parse(tag))
+	}
+}
+
+impl From<&str> for Tag {
+	fn from(tag: &str) -> Self {
+		Self::parse(tag)
+	}
+}
+
+impl Serialize for Tag {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.0)
+	}
+}
+
+impl<'de> Deserialize<'de> for Tag {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		String::deserialize(deserializer).map(|tag| Self::parse(&tag))
+	}
+}
+
+/// Seconds from epoch as a UTC ISO 8601 date-time, without depending on a date library
+fn iso8601_from_epoch_seconds(seconds: u64) -> String {
+	let (days, time) = (seconds / 86_400, seconds % 86_400);
+
+	// days to a civil date, from Howard Hinnant's `civil_from_days`
+	let z = days + 719_468;
+	let era = z / 146_097;
+	let day_of_era = z % 146_097;
+	let year_of_era =
+		(day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let shifted_month = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+	let month = if shifted_month < 10 {
+		shifted_month + 3
+	} else {
+		shifted_month - 9
+	};
+	let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+	format!(
+		"{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+		time / 3_600,
+		time / 60 % 60,
+		time % 60
+	)
 }
 
-#[derive(Serialize)]
+/// Request body for creating a post
+#[serde_with::serde_as]
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Debug, Default, PartialEq)]
 pub struct Post {
-	pub id: u64,
+	pub content: Vec<ContentBlock>,
+	/// if None, blocks are displayed in order
+	pub layout: Option<Vec<LayoutBlock>>,
+	/// if false, the post is published but not shown on the blog's timeline.  Tumblr defaults to true
+	pub add_to_timeline: Option<bool>,
+	/// hide the post from search results.  Tumblr defaults to false
+	pub exclude_from_search: Option<bool>,
+	/// the end of the post's URL.  Only lowercase letters, digits, and hyphens are allowed
+	pub slug: Option<String>,
+	/// Tumblr defaults to [`PostState::Published`]
+	pub state: Option<PostState>,
+	/// when a [`PostState::Queue`] post goes live, as an ISO 8601 date-time (e.g. `2022-06-01T12:00:00Z`).
+	/// Otherwise it's published at the queue's next slot
+	pub publish_on: Option<String>,
+	/// for an answer to an ask, only the asker and the blog can see it.
+	/// Not the same as [`PostState::Private`], which hides a post from everyone but the blog.  Only allowed with an ask in the layout
+	pub is_private: Option<bool>,
+	/// sent comma-separated, so a tag can't contain a comma
+	#[serde_as(as = "Option<serde_with::StringWithSeparator<serde_with::CommaSeparator, Tag>>")]
+	pub tags: Option<Vec<Tag>>,
+	/// mature themes in the post, which hide it from users who filter them.  `Some(vec![])` removes every label
+	pub community_labels: Option<Vec<CommunityLabel>>,
+	/// extra fields merged into the request body, for options this crate doesn't model yet.
+	/// The typed fields above take precedence over a key of the same name here
+	#[serde(skip)]
+	pub post_options: Option<HashMap<String, serde_json::Value>>,
 }
 
-#[cfg(test)]
-mod tests {
-	use pretty_assertions::assert_eq;
-	use serde_json::{from_str, to_string_pretty};
+/// Counts summarizing a [`Post`], see [`Post::stats`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PostStats {
+	pub block_count: usize,
+	/// total length of the text blocks, in chars rather than bytes
+	pub text_char_count: usize,
+	/// number of image blocks, which includes GIFs
+	pub image_count: usize,
+	pub has_video: bool,
+	pub tag_count: usize,
+}
 
-	use super::*;
+impl Post {
+	/// An answer to an ask, for [`Client::answer_ask`](crate::Client::answer_ask).
+	/// `ask_content` comes first and is marked as the ask in the layout, followed by `answer`.
+	/// `ask_blog` is the asker, or `None` if the ask was anonymous
+	pub fn answer_ask(
+		ask_content: Vec<ContentBlock>,
+		ask_blog: Option<BlogInfo>,
+		answer: Vec<ContentBlock>,
+	) -> Self {
+		let ask_blocks = (0..ask_content.len() as u64).collect();
 
-	macro_rules! s {
-		($str: expr) => {
-			$str.to_owned()
+		let mut content = ask_content;
+		content.extend(answer);
+
+		Self {
+			content,
+			layout: Some(vec![LayoutBlock::Ask {
+				blocks: ask_blocks,
+				attribution: ask_blog.map(|blog| Attribution::Blog { blog }),
+			}]),
+			..Default::default()
+		}
+	}
+
+	/// Adds a "keep reading" break after the content block at `block_index`.
+	/// Returns [`Error::Validation`] unless there's at least one block after it
+	pub fn read_more_after(mut self, block_index: usize) -> crate::Result<Self> {
+		if block_index + 1 >= self.content.len() {
+			return Err(Error::Validation(format!(
+				"read more break after block {block_index}, but the post only has {} blocks",
+				self.content.len()
+			)));
+		}
+
+		let truncate_after = Some(block_index as u64);
+		let layout = self.layout.get_or_insert_with(Vec::new);
+
+		match layout
+			.iter_mut()
+			.find(|block| matches!(block, LayoutBlock::Rows { .. }))
+		{
+			Some(LayoutBlock::Rows {
+				truncate_after: existing,
+				..
+			}) => *existing = truncate_after,
+			_ => layout.push(LayoutBlock::Rows {
+				blocks: (0..self.content.len() as u64)
+					.map(|block| RowDisplay {
+						blocks: vec![block],
+						mode: None,
+					})
+					.collect(),
+				truncate_after,
+			}),
+		}
+
+		Ok(self)
+	}
+
+	/// Returns [`Error::Validation`] for a post Tumblr would reject, e.g. a layout pointing past the end of `content`.
+	/// Called before every request
+	pub fn validate(&self) -> crate::Result<()> {
+		let check_index = |index: u64| {
+			if index < self.content.len() as u64 {
+				Ok(())
+			} else {
+				Err(Error::Validation(format!(
+					"layout refers to block {index}, but the post only has {} blocks",
+					self.content.len()
+				)))
+			}
 		};
+
+		for layout in self.layout.iter().flatten() {
+			match layout {
+				LayoutBlock::Rows { blocks, .. } => blocks
+					.iter()
+					.flat_map(|row| &row.blocks)
+					.try_for_each(|&index| check_index(index))?,
+				LayoutBlock::Condensed {
+					blocks,
+					truncate_after,
+				} => {
+					if blocks.is_none() && truncate_after.is_none() {
+						return Err(Error::Validation(
+							"condensed layout needs either blocks or truncate_after".to_owned(),
+						));
+					}
+
+					for (expected, &index) in blocks.iter().flatten().enumerate() {
+						if index != expected as u64 {
+							return Err(Error::Validation(format!(
+								"condensed layout must list blocks 0, 1, 2, ... in order, but has {index} at position {expected}"
+							)));
+						}
+						check_index(index)?;
+					}
+				}
+				LayoutBlock::Ask { blocks, .. } => {
+					blocks.iter().try_for_each(|&index| check_index(index))?
+				}
+			}
+		}
+
+		if let Some(slug) = &self.slug {
+			let allowed = |ch| matches!(ch, 'a'..='z' | '0'..='9' | '-');
+
+			if slug.is_empty() || !slug.chars().all(allowed) {
+				return Err(Error::Validation(format!(
+					"slug {slug:?} may only contain lowercase letters, digits, and hyphens"
+				)));
+			}
+		}
+
+		let has_ask = self
+			.layout
+			.iter()
+			.flatten()
+			.any(|block| matches!(block, LayoutBlock::Ask { .. }));
+
+		if self.is_private == Some(true) && !has_ask {
+			return Err(Error::Validation("only answers to asks can be private".to_owned()));
+		}
+
+		if self.publish_on.is_some() && self.state != Some(PostState::Queue) {
+			return Err(Error::Validation("publish_on is only allowed for queued posts".to_owned()));
+		}
+
+		if let Some(tag) = self
+			.tags
+			.iter()
+			.flatten()
+			.find(|tag| tag.as_str().is_empty() || tag.as_str().contains(','))
+		{
+			return Err(Error::Validation(format!(
+				"tag {:?} must be non-empty and can't contain a comma",
+				tag.as_str()
+			)));
+		}
+
+		Ok(())
 	}
 
-	fn assert_serde<'de, T: Serialize + Deserialize<'de> + std::fmt::Debug + PartialEq>(
-		str: &'static str,
-		obj: T,
-	) {
-		assert_eq!(from_str::<'de, T>(str).unwrap(), obj);
-		assert_eq!(to_string_pretty(&obj).unwrap(), str);
+	/// Counts the post's blocks, text, images and tags, without serializing or rendering it
+	pub fn stats(&self) -> PostStats {
+		let mut stats = PostStats {
+			block_count: self.content.len(),
+			tag_count: self.tags.as_ref().map_or(0, Vec::len),
+			..Default::default()
+		};
+
+		for block in &self.content {
+			match block {
+				ContentBlock::Text { text, .. } => stats.text_char_count += text.chars().count(),
+				ContentBlock::Image { .. } => stats.image_count += 1,
+				ContentBlock::Video { .. } => stats.has_video = true,
+				_ => (),
+			}
+		}
+
+		stats
 	}
 
-	#[test]
-	fn test_text_blocks() {
-		assert_eq!(
-			from_str::<ContentBlock>(
-				r#"{
-				"type": "text",
-				"text": "Hello world!"
-			}"#,
-			)
-			.unwrap(),
-			ContentBlock::Text {
-				text: "Hello world!".to_owned(),
-				subtype: None,
-				indent_level: None,
-				formatting: None
+	/// The JSON request body, with [`Post::post_options`] merged in and retrieve-only fields removed
+	pub(crate) fn to_body(&self) -> crate::Result<serde_json::Value> {
+		self.validate()?;
+
+		let mut body = crate::to_json(self)?;
+
+		// `display_url` is generated by Tumblr, so don't send one copied from a fetched post
+		if let Some(serde_json::Value::Array(content)) = body.get_mut("content") {
+			for block in content {
+				if block["type"] == "link" {
+					if let serde_json::Value::Object(block) = block {
+						block.remove("display_url");
+					}
+				}
 			}
-		);
+		}
 
-		assert_eq!(
-			from_str::<Vec<ContentBlock>>(
-				r#"[
-			{
-					"type": "text",
-					"subtype": "heading1",
-					"text": "Sward's Shopping List"
-			},
-			{
-					"type": "text",
-					"subtype": "ordered-list-item",
-					"text": "First level: Fruit"
-			},
-			{
-					"type": "text",
-					"subtype": "unordered-list-item",
-					"text": "Second level: Apples",
-					"indent_level": 1
-			},
-			{
-					"type": "text",
-					"subtype": "ordered-list-item",
-					"text": "Third Level: Green",
-					"indent_level": 2
-			},
-			{
-					"type": "text",
-					"subtype": "unordered-list-item",
-					"text": "Second level: Pears",
-					"indent_level": 1
-			},
-			{
-					"type": "text",
-					"subtype": "ordered-list-item",
-					"text": "First level: Pears"
+		if let (serde_json::Value::Object(body), Some(options)) = (&mut body, &self.post_options) {
+			for (key, value) in options {
+				body.entry(key.clone()).or_insert_with(|| value.clone());
 			}
-	]"#
-			)
-			.unwrap(),
-			vec![
-				ContentBlock::Text {
-					text: "Sward's Shopping List".to_owned(),
-					subtype: Some(TextSubtype::Heading1),
-					indent_level: None,
+		}
+
+		Ok(body)
+	}
+}
+
+/// Response to a successful [`Client::create_post`](crate::Client::create_post)
+#[serde_with::serde_as]
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct CreatedPost {
+	#[serde_as(as = "serde_with::DisplayFromStr")]
+	pub id: u64,
+}
+
+/// A post as returned by the API in NPF format.
+/// Serializes back to the same shape, e.g. for [`Blog::export_ndjson`](crate::blog::Blog::export_ndjson)
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct FetchedPost {
+	pub id: u64,
+	pub blog_name: String,
+	pub blog: Option<BlogInfo>,
+	/// seconds from epoch
+	pub timestamp: u64,
+	/// e.g. `published`, `queued`, `draft` or `private`.
+	/// Not a [`PostState`], since the API reports queued posts as `queued` and can send states that can't be set, like `submission`
+	pub state: Option<String>,
+	/// short plaintext summary of the post, for list views
+	pub summary: Option<String>,
+	/// the slug Tumblr appends to the post URL
+	pub slug: Option<String>,
+	/// needed to reblog or like the post
+	pub reblog_key: Option<String>,
+	/// whether the authenticated user may reblog the post, see [`FetchedPost::can_reblog`]
+	pub can_reblog: Option<bool>,
+	/// who the post's author allows to reblog it
+	pub interactability_reblog: Option<Interactability>,
+	/// when a queued post will be published, in seconds from epoch.  Only sent for queued posts
+	pub scheduled_publish_time: Option<u64>,
+	/// whether an answer is only visible to the asker and the blog, see [`Post::is_private`]
+	pub is_private: Option<bool>,
+	/// the post's content labels, see [`Post::community_labels`]
+	pub community_labels: Option<Vec<CommunityLabel>>,
+	#[serde(default)]
+	pub content: Vec<ContentBlock>,
+	#[serde(default)]
+	pub layout: Vec<LayoutBlock>,
+	/// the posts this one is a reblog of, root first.  Empty for original posts
+	#[serde(default)]
+	pub trail: Vec<ReblogTrail>,
+	#[serde(default)]
+	pub tags: Vec<Tag>,
+	/// total notes (likes, reblogs and replies)
+	pub note_count: Option<u64>,
+	/// only sent on some endpoints, see [`FetchedPost::note_count`] for the total
+	pub like_count: Option<u64>,
+	pub reblog_count: Option<u64>,
+	pub reply_count: Option<u64>,
+	/// whether the authenticated user has liked the post, e.g. for a like button.
+	/// Only sent on authenticated requests
+	pub liked: Option<bool>,
+	/// only sent with [`GetPostsOptions::reblog_info`](crate::blog::GetPostsOptions::reblog_info)
+	#[serde(flatten)]
+	pub reblog_info: ReblogInfo,
+	/// only sent with [`GetPostsOptions::notes_info`](crate::blog::GetPostsOptions::notes_info), and only the most recent notes
+	pub notes: Option<Vec<Note>>,
+	/// the legacy post type, e.g. `note` for answers
+	pub original_type: Option<String>,
+	/// who sent the ask an answer post is replying to.  Absent for anonymous asks
+	pub asking_name: Option<String>,
+	/// the asker's blog URL.  Absent for anonymous asks
+	pub asking_url: Option<String>,
+	/// whether the ask was sent anonymously.  Only sent for answer posts
+	pub is_anonymous: Option<bool>,
+	/// every field this crate doesn't model yet, as sent
+	#[serde(flatten)]
+	pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl FetchedPost {
+	/// [`FetchedPost::state`] as the [`PostState`] to send back when editing, or `None` for states that can't be set
+	pub fn post_state(&self) -> Option<PostState> {
+		match self.state.as_deref()? {
+			"published" => Some(PostState::Published),
+			"queued" | "queue" => Some(PostState::Queue),
+			"draft" => Some(PostState::Draft),
+			"private" => Some(PostState::Private),
+			_ => None,
+		}
+	}
+
+	/// [`FetchedPost::scheduled_publish_time`] as the ISO 8601 date-time [`Post::publish_on`] takes, e.g. `2022-06-01T12:00:00Z`
+	pub fn publish_on(&self) -> Option<String> {
+		self.scheduled_publish_time.map(iso8601_from_epoch_seconds)
+	}
+
+	/// Whether to offer a reblog button: the post has a `reblog_key`, and neither `can_reblog` nor `interactability_reblog` forbid it.
+	/// Fields the API didn't send are assumed to allow reblogging
+	pub fn can_reblog(&self) -> bool {
+		self.reblog_key.is_some()
+			&& self.can_reblog != Some(false)
+			&& self.interactability_reblog != Some(Interactability::Noone)
+	}
+
+	/// Orders posts oldest first, for use with e.g. [`slice::sort_by`]
+	pub fn by_timestamp(a: &Self, b: &Self) -> Ordering {
+		a.timestamp.cmp(&b.timestamp)
+	}
+
+	/// Orders queued posts by when they go live, soonest first, and posts that aren't scheduled last
+	pub fn by_scheduled_publish_time(a: &Self, b: &Self) -> Ordering {
+		match (a.scheduled_publish_time, b.scheduled_publish_time) {
+			(Some(a), Some(b)) => a.cmp(&b),
+			(Some(_), None) => Ordering::Less,
+			(None, Some(_)) => Ordering::Greater,
+			(None, None) => Ordering::Equal,
+		}
+	}
+
+	/// Flattens the reblog trail and this post's own content into [`TrailEntry`]s, from the root post to this one
+	pub fn into_ancestry(self) -> Vec<TrailEntry> {
+		let mut ancestry: Vec<_> = self
+			.trail
+			.into_iter()
+			.map(|trail| match trail {
+				ReblogTrail::Ok {
+					post,
+					blog,
+					content,
+					layout,
+				} => TrailEntry {
+					blog_name: blog.name.clone(),
+					blog: Some(blog),
+					post: Some(post),
+					content,
+					layout,
+					broken: false,
+				},
+				ReblogTrail::Broken {
+					broken_blog_name,
+					content,
+					layout,
+				} => TrailEntry {
+					blog_name: Some(broken_blog_name),
+					blog: None,
+					post: None,
+					content,
+					layout,
+					broken: true,
+				},
+				ReblogTrail::Reference { post, blog } => TrailEntry {
+					blog_name: blog.name.clone(),
+					blog: Some(blog),
+					post: Some(post),
+					content: vec![],
+					layout: vec![],
+					broken: false,
+				},
+			})
+			.collect();
+
+		ancestry.push(TrailEntry {
+			blog_name: Some(self.blog_name),
+			blog: self.blog,
+			post: Some(PostInfo { id: self.id }),
+			content: self.content,
+			layout: self.layout,
+			broken: false,
+		});
+
+		ancestry
+	}
+}
+
+/// Where a reblogged post came from.  All `None` for original posts or when `reblog_info` wasn't requested
+#[serde_with::serde_as]
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ReblogInfo {
+	/// the post this one was reblogged from
+	#[serde_as(as = "Option<serde_with::DisplayFromStr>")]
+	#[serde(default)]
+	pub reblogged_from_id: Option<u64>,
+	pub reblogged_from_name: Option<String>,
+	pub reblogged_from_uuid: Option<String>,
+	pub reblogged_from_url: Option<String>,
+	pub reblogged_from_title: Option<String>,
+	/// the start of the reblog chain
+	#[serde_as(as = "Option<serde_with::DisplayFromStr>")]
+	#[serde(default)]
+	pub reblogged_root_id: Option<u64>,
+	pub reblogged_root_name: Option<String>,
+	pub reblogged_root_uuid: Option<String>,
+	pub reblogged_root_url: Option<String>,
+	pub reblogged_root_title: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "String", into = "String")]
+pub enum NoteType {
+	Like,
+	Reblog,
+	/// the original post
+	Posted,
+	Reply,
+	/// a note type this crate doesn't know yet, kept as sent
+	Other(String),
+}
+
+impl From<String> for NoteType {
+	fn from(note_type: String) -> Self {
+		match note_type.as_str() {
+			"like" => Self::Like,
+			"reblog" => Self::Reblog,
+			"posted" => Self::Posted,
+			"reply" => Self::Reply,
+			_ => Self::Other(note_type),
+		}
+	}
+}
+
+impl From<NoteType> for String {
+	fn from(note_type: NoteType) -> Self {
+		match note_type {
+			NoteType::Like => "like".to_owned(),
+			NoteType::Reblog => "reblog".to_owned(),
+			NoteType::Posted => "posted".to_owned(),
+			NoteType::Reply => "reply".to_owned(),
+			NoteType::Other(note_type) => note_type,
+		}
+	}
+}
+
+/// Who may interact with a post, see [`FetchedPost::interactability_reblog`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "String", into = "String")]
+pub enum Interactability {
+	Everyone,
+	Noone,
+	/// a setting this crate doesn't know yet, kept as sent
+	Other(String),
+}
+
+impl From<String> for Interactability {
+	fn from(interactability: String) -> Self {
+		match interactability.as_str() {
+			"everyone" => Self::Everyone,
+			"noone" => Self::Noone,
+			_ => Self::Other(interactability),
+		}
+	}
+}
+
+impl From<Interactability> for String {
+	fn from(interactability: Interactability) -> Self {
+		match interactability {
+			Interactability::Everyone => "everyone".to_owned(),
+			Interactability::Noone => "noone".to_owned(),
+			Interactability::Other(interactability) => interactability,
+		}
+	}
+}
+
+/// A like, reblog, or reply on a post, see [`FetchedPost::notes`]
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Note {
+	#[serde(rename = "type")]
+	pub note_type: NoteType,
+	/// seconds from epoch
+	pub timestamp: u64,
+	pub blog_name: String,
+	pub blog_uuid: Option<String>,
+	pub blog_url: Option<String>,
+	/// the reblog's post id, for [`NoteType::Reblog`]
+	pub post_id: Option<String>,
+	/// for [`NoteType::Reply`]
+	pub reply_text: Option<String>,
+}
+
+/// Notes on a post, see [`Blog::notes`](crate::blog::Blog::notes)
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct NotesPage {
+	#[serde(default)]
+	pub notes: Vec<Note>,
+	/// the notes left out of [`NotesPage::notes`].  Only sent with [`NotesMode::Rollup`](crate::blog::NotesMode::Rollup)
+	pub rollup_notes: Option<Vec<Note>>,
+	/// total notes on the post, not just this page
+	pub total_notes: Option<u64>,
+	/// only sent with [`NotesMode::Rollup`](crate::blog::NotesMode::Rollup)
+	pub total_likes: Option<u64>,
+	/// only sent with [`NotesMode::Rollup`](crate::blog::NotesMode::Rollup)
+	pub total_reblogs: Option<u64>,
+}
+
+/// One post in a reblog chain, see [`FetchedPost::into_ancestry`].
+/// `content` and `layout` are empty for entries fetched with [`TrailMode::Compact`]
+#[derive(Debug, PartialEq)]
+pub struct TrailEntry {
+	/// for broken entries, the name the blog had before it was deleted
+	pub blog_name: Option<String>,
+	/// `None` if the entry is broken
+	pub blog: Option<BlogInfo>,
+	/// `None` if the entry is broken
+	pub post: Option<PostInfo>,
+	pub content: Vec<ContentBlock>,
+	pub layout: Vec<LayoutBlock>,
+	/// the original blog or post has been deleted
+	pub broken: bool,
+}
+
+/// A page of posts from a listing endpoint (e.g., `blog/{blog_identifier}/posts`)
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct PostsPage<P = FetchedPost> {
+	pub posts: Vec<P>,
+	/// total number of posts in the listing, not just this page
+	pub total_posts: Option<u64>,
+	/// the next and previous pages, if any
+	#[serde(rename = "_links")]
+	pub links: Option<PageLinks>,
+}
+
+/// Links to the pages around a [`PostsPage`]
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct PageLinks {
+	pub next: Option<PageLink>,
+	pub prev: Option<PageLink>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct PageLink {
+	/// including the API version, e.g. `/v2/blog/staff/posts?offset=20`
+	pub href: String,
+	/// the query of `href`, which is all that changes between pages
+	#[serde(default)]
+	pub query_params: BTreeMap<String, serde_json::Value>,
+}
+
+impl<P> PostsPage<P> {
+	#[inline]
+	pub fn iter(&self) -> std::slice::Iter<'_, P> {
+		self.posts.iter()
+	}
+
+	/// number of posts on this page, see [`PostsPage::total_posts`] for the whole listing
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.posts.len()
+	}
+
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.posts.is_empty()
+	}
+}
+
+impl<P> IntoIterator for PostsPage<P> {
+	type Item = P;
+	type IntoIter = std::vec::IntoIter<P>;
+
+	#[inline]
+	fn into_iter(self) -> Self::IntoIter {
+		self.posts.into_iter()
+	}
+}
+
+impl<'a, P> IntoIterator for &'a PostsPage<P> {
+	type Item = &'a P;
+	type IntoIter = std::slice::Iter<'a, P>;
+
+	#[inline]
+	fn into_iter(self) -> Self::IntoIter {
+		self.posts.iter()
+	}
+}
+
+/// A page of a blog's liked posts, see [`Blog::likes`](crate::blog::Blog::likes)
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct LikesPage {
+	pub liked_posts: Vec<FetchedPost>,
+	/// total number of posts the blog has liked
+	pub liked_count: Option<u64>,
+}
+
+/// A page of posts parsed one at a time, so a post this crate can't parse doesn't fail the rest of the page.
+/// See [`Blog::get_posts_lenient`](crate::blog::Blog::get_posts_lenient)
+#[derive(Deserialize, Debug)]
+#[serde(from = "PostsPage<serde_json::Value>")]
+pub struct LenientPostsPage {
+	/// in the order sent, with an `Err` for each post that couldn't be parsed
+	pub posts: Vec<Result<FetchedPost, PostParseError>>,
+	/// total number of posts in the listing, not just this page
+	pub total_posts: Option<u64>,
+}
+
+impl From<PostsPage<serde_json::Value>> for LenientPostsPage {
+	fn from(page: PostsPage<serde_json::Value>) -> Self {
+		Self {
+			posts: page
+				.posts
+				.into_iter()
+				.map(|raw| {
+					serde_json::from_value(raw.clone()).map_err(|error| PostParseError {
+						id: raw.get("id").and_then(serde_json::Value::as_u64),
+						error,
+						raw,
+					})
+				})
+				.collect(),
+			total_posts: page.total_posts,
+		}
+	}
+}
+
+/// A post in a [`LenientPostsPage`] that couldn't be parsed as a [`FetchedPost`]
+#[derive(Debug, thiserror::Error)]
+#[error("Error parsing post {id:?}")]
+pub struct PostParseError {
+	/// `None` if the post didn't have a numeric `id`
+	pub id: Option<u64>,
+	#[source]
+	pub error: serde_json::Error,
+	/// the post as sent, to archive as is
+	pub raw: serde_json::Value,
+}
+
+#[cfg(test)]
+mod tests {
+	use pretty_assertions::assert_eq;
+	use serde_json::{from_str, to_string_pretty};
+
+	use super::*;
+
+	macro_rules! s {
+		($str: expr) => {
+			$str.to_owned()
+		};
+	}
+
+	fn assert_serde<'de, T: Serialize + Deserialize<'de> + std::fmt::Debug + PartialEq>(
+		str: &'static str,
+		obj: T,
+	) {
+		assert_eq!(from_str::<'de, T>(str).unwrap(), obj);
+		assert_eq!(to_string_pretty(&obj).unwrap(), str);
+	}
+
+	#[test]
+	fn test_media_optional_fields() {
+		let url = "https://69.media.tumblr.com/path/to/image.jpg";
+		let bare = || Media {
+			url: s!(url),
+			mime_type: None,
+			width: None,
+			height: None,
+			identifier: None,
+		};
+
+		// absent
+		assert_serde(
+			r#"{
+  "url": "https://69.media.tumblr.com/path/to/image.jpg"
+}"#,
+			bare(),
+		);
+
+		// null deserializes like absent, and is dropped when serialized again
+		let nulls: Media = from_str(
+			r#"{ "url": "https://69.media.tumblr.com/path/to/image.jpg", "type": null, "width": null, "height": null }"#,
+		)
+		.unwrap();
+		assert_eq!(nulls, bare());
+		assert_eq!(
+			serde_json::to_value(&nulls).unwrap(),
+			serde_json::json!({ "url": url })
+		);
+
+		// present
+		assert_serde(
+			r#"{
+  "url": "https://69.media.tumblr.com/path/to/image.jpg",
+  "type": "image/jpeg",
+  "width": 540,
+  "height": 405
+}"#,
+			Media {
+				url: s!(url),
+				mime_type: Some(s!("image/jpeg")),
+				width: Some(540),
+				height: Some(405),
+				identifier: None,
+			},
+		);
+	}
+
+	#[test]
+	fn test_text_blocks() {
+		assert_eq!(
+			from_str::<ContentBlock>(
+				r#"{
+				"type": "text",
+				"text": "Hello world!"
+			}"#,
+			)
+			.unwrap(),
+			ContentBlock::Text {
+				text: "Hello world!".to_owned(),
+				subtype: None,
+				indent_level: None,
+				formatting: None
+			}
+		);
+
+		assert_eq!(
+			from_str::<Vec<ContentBlock>>(
+				r#"[
+			{
+					"type": "text",
+					"subtype": "heading1",
+					"text": "Sward's Shopping List"
+			},
+			{
+					"type": "text",
+					"subtype": "ordered-list-item",
+					"text": "First level: Fruit"
+			},
+			{
+					"type": "text",
+					"subtype": "unordered-list-item",
+					"text": "Second level: Apples",
+					"indent_level": 1
+			},
+			{
+					"type": "text",
+					"subtype": "ordered-list-item",
+					"text": "Third Level: Green",
+					"indent_level": 2
+			},
+			{
+					"type": "text",
+					"subtype": "unordered-list-item",
+					"text": "Second level: Pears",
+					"indent_level": 1
+			},
+			{
+					"type": "text",
+					"subtype": "ordered-list-item",
+					"text": "First level: Pears"
+			}
+	]"#
+			)
+			.unwrap(),
+			vec![
+				ContentBlock::Text {
+					text: "Sward's Shopping List".to_owned(),
+					subtype: Some(TextSubtype::Heading1),
+					indent_level: None,
 					formatting: None
 				},
 				ContentBlock::Text {
@@ -420,7 +1615,8 @@ mod tests {
 					end: 19,
 					format_type: TextFormatType::Mention {
 						blog: BlogInfo {
-							uuid: "t:123456abcdf".to_owned()
+							uuid: "t:123456abcdf".to_owned(),
+							name: Some("david".to_owned()),
 						}
 					}
 				}])
@@ -447,7 +1643,10 @@ mod tests {
 			LayoutBlock::Ask {
 				blocks: vec![0, 1],
 				attribution: Some(Attribution::Blog {
-					blog: BlogInfo { uuid: s!("abcdef") },
+					blog: BlogInfo {
+						uuid: s!("abcdef"),
+						name: None,
+					},
 				}),
 			},
 		);
@@ -523,7 +1722,7 @@ mod tests {
 }"#,
 			 Attribution::Post {
 				 url: s!("http://www.davidslog.com/153957802620/five-years-of-working-with-this-awesome-girl"),
-				 blog: BlogInfo { uuid: s!("t:123456abcdf") },
+				 blog: BlogInfo { uuid: s!("t:123456abcdf"), name: None },
 				 post: PostInfo { id: 1234567890 }
 				}
 			);
@@ -560,8 +1759,1064 @@ mod tests {
 					mime_type: Some(s!("image/jpeg")),
 					width: Some(64),
 					height: Some(64),
+					identifier: None,
+				}),
+			},
+		);
+	}
+
+	#[test]
+	fn test_image_block_round_trip() {
+		assert_serde(
+			r#"{
+  "type": "image",
+  "media": [
+    {
+      "url": "https://69.media.tumblr.com/path/to/image.gif",
+      "type": "image/gif",
+      "width": 400,
+      "height": 300
+    }
+  ],
+  "feedback_token": "abcdef123456",
+  "poster": {
+    "url": "https://69.media.tumblr.com/path/to/poster.jpg",
+    "type": "image/jpeg",
+    "width": 400,
+    "height": 300
+  },
+  "alt_text": "a corgi running"
+}"#,
+			ContentBlock::Image {
+				media: vec![Media {
+					url: s!("https://69.media.tumblr.com/path/to/image.gif"),
+					mime_type: Some(s!("image/gif")),
+					width: Some(400),
+					height: Some(300),
+					identifier: None,
+				}],
+				feedback_token: Some(s!("abcdef123456")),
+				poster: Some(Media {
+					url: s!("https://69.media.tumblr.com/path/to/poster.jpg"),
+					mime_type: Some(s!("image/jpeg")),
+					width: Some(400),
+					height: Some(300),
+					identifier: None,
 				}),
+				attribution: None,
+				alt_text: Some(s!("a corgi running")),
+				caption: None,
+			},
+		);
+	}
+
+	#[test]
+	fn test_video_can_autoplay_on_cellular() {
+		let video = |can_autoplay_on_cellular| ContentBlock::Video {
+			source: MediaSource::Url {
+				url: s!("https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
 			},
+			embed_html: None,
+			embed_iframe: None,
+			embed_url: None,
+			poster: None,
+			attribution: None,
+			can_autoplay_on_cellular,
+		};
+
+		assert_serde(
+			r#"{
+  "type": "video",
+  "source": {
+    "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+  },
+  "can_autoplay_on_cellular": true
+}"#,
+			video(Some(true)),
+		);
+
+		assert_serde(
+			r#"{
+  "type": "video",
+  "source": {
+    "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+  },
+  "can_autoplay_on_cellular": false
+}"#,
+			video(Some(false)),
+		);
+
+		assert_serde(
+			r#"{
+  "type": "video",
+  "source": {
+    "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+  }
+}"#,
+			video(None),
+		);
+	}
+
+	#[test]
+	fn test_tag() {
+		let tag = Tag::parse("#Foo");
+
+		assert_eq!(tag, Tag::parse("  foo "));
+		assert_eq!(tag.to_string(), "Foo");
+		assert_eq!(
+			std::collections::HashSet::from([tag, Tag::parse("FOO")]).len(),
+			1
+		);
+		assert_ne!(Tag::parse("foo"), Tag::parse("foo bar"));
+		assert_eq!(Tag::parse("Foo").cmp(&Tag::parse("foo")), Ordering::Equal);
+		assert!(Tag::parse("apple") < Tag::parse("Banana"));
+		assert_eq!(
+			std::collections::BTreeSet::from([Tag::parse("Foo"), Tag::parse("foo")]).len(),
+			1
+		);
+
+		let post: FetchedPost = from_str(
+			r##"{"id": 1, "blog_name": "staff", "timestamp": 0, "tags": ["corgi", "#Dogs"]}"##,
+		)
+		.unwrap();
+		assert_eq!(post.tags, vec![Tag::parse("corgi"), Tag::parse("dogs")]);
+
+		let body = Post {
+			tags: Some(vec![Tag::parse("#corgi"), Tag::parse(" dogs ")]),
+			..Default::default()
+		}
+		.to_body()
+		.unwrap();
+		assert_eq!(body["tags"], "corgi,dogs");
+
+		assert!(matches!(
+			Post {
+				tags: Some(vec![Tag::parse("a,b")]),
+				..Default::default()
+			}
+			.to_body(),
+			Err(Error::Validation(_))
+		));
+	}
+
+	#[test]
+	fn test_link_block() {
+		let block = ContentBlock::link(
+			s!("https://example.com/corgis"),
+			Some(s!("Corgi facts")),
+			Some(s!("Everything about corgis")),
+			Some(Media::new(
+				s!("https://example.com/corgi.jpg"),
+				Some(1200),
+				Some(630),
+			)),
+		)
+		.unwrap();
+
+		assert_eq!(
+			serde_json::to_value(&block).unwrap(),
+			serde_json::json!({
+				"type": "link",
+				"url": "https://example.com/corgis",
+				"title": "Corgi facts",
+				"description": "Everything about corgis",
+				"poster": {
+					"url": "https://example.com/corgi.jpg",
+					"width": 1200,
+					"height": 630,
+				},
+			})
+		);
+
+		assert!(matches!(
+			ContentBlock::link(s!("javascript:alert(1)"), None, None, None),
+			Err(Error::Validation(_))
+		));
+		assert!(matches!(
+			ContentBlock::link(
+				s!("https://example.com"),
+				None,
+				None,
+				Some(Media::new(s!("not a url"), None, None))
+			),
+			Err(Error::Validation(_))
+		));
+	}
+
+	#[test]
+	fn test_semantic_eq() {
+		let created: ContentBlock = from_str(
+			r#"{
+	"type": "image",
+	"media": [{ "url": "https://69.media.tumblr.com/path/to/image.jpg" }],
+	"alt_text": "a corgi"
+}"#,
+		)
+		.unwrap();
+		let stored: ContentBlock = from_str(
+			r#"{
+	"type": "image",
+	"media": [{ "url": "https://69.media.tumblr.com/path/to/image.jpg", "width": 540, "height": 405 }],
+	"feedback_token": "abcdef123456",
+	"alt_text": "a corgi"
+}"#,
+		)
+		.unwrap();
+
+		assert_ne!(created, stored);
+		assert!(created.semantic_eq(&stored));
+
+		let created: ContentBlock =
+			from_str(r#"{ "type": "link", "url": "https://example.com/article" }"#).unwrap();
+		let stored: ContentBlock = from_str(
+			r#"{ "type": "link", "url": "https://example.com/article", "display_url": "example.com/article" }"#,
+		)
+		.unwrap();
+
+		assert!(created.semantic_eq(&stored));
+
+		let edited: ContentBlock =
+			from_str(r#"{ "type": "link", "url": "https://example.com/other" }"#).unwrap();
+
+		assert!(!edited.semantic_eq(&stored));
+	}
+
+	#[test]
+	fn test_post_options() {
+		let content = || {
+			vec![ContentBlock::Text {
+				text: s!("hi"),
+				subtype: None,
+				indent_level: None,
+				formatting: None,
+			}]
+		};
+
+		assert_eq!(
+			to_string_pretty(&Post {
+				content: content(),
+				..Default::default()
+			})
+			.unwrap(),
+			r#"{
+  "content": [
+    {
+      "type": "text",
+      "text": "hi"
+    }
+  ]
+}"#
+		);
+
+		assert_eq!(
+			to_string_pretty(&Post {
+				content: content(),
+				add_to_timeline: Some(false),
+				exclude_from_search: Some(true),
+				..Default::default()
+			})
+			.unwrap(),
+			r#"{
+  "content": [
+    {
+      "type": "text",
+      "text": "hi"
+    }
+  ],
+  "add_to_timeline": false,
+  "exclude_from_search": true
+}"#
+		);
+	}
+
+	#[test]
+	fn test_posts_page_into_iter() {
+		let page = from_str::<PostsPage>(
+			r#"{
+	"posts": [
+		{ "id": 1, "blog_name": "staff", "timestamp": 1654041600 },
+		{ "id": 2, "blog_name": "staff", "timestamp": 1654041700 }
+	],
+	"total_posts": 40
+}"#,
+		)
+		.unwrap();
+
+		let mut ids = vec![];
+		for post in &page {
+			ids.push(post.id);
+		}
+		assert_eq!(ids, vec![1, 2]);
+		assert_eq!(page.len(), 2);
+		assert_eq!(page.total_posts, Some(40));
+
+		assert_eq!(
+			page.into_iter().map(|post| post.id).collect::<Vec<_>>(),
+			vec![1, 2]
+		);
+	}
+
+	#[test]
+	fn test_posts_page_note_counts() {
+		let page = from_str::<PostsPage>(
+			r#"{
+	"posts": [
+		{
+			"id": 1234,
+			"blog_name": "staff",
+			"timestamp": 1654041600,
+			"content": [{ "type": "text", "text": "first" }],
+			"layout": [],
+			"note_count": 42,
+			"like_count": 30,
+			"reblog_count": 10,
+			"reply_count": 2
+		},
+		{
+			"id": 1235,
+			"blog_name": "staff",
+			"timestamp": 1654041600,
+			"content": [],
+			"layout": [],
+			"note_count": 0
+		}
+	],
+	"total_posts": 2
+}"#,
+		)
+		.unwrap();
+
+		assert_eq!(page.total_posts, Some(2));
+		assert_eq!(page.posts.len(), 2);
+
+		let first = &page.posts[0];
+		assert_eq!(first.id, 1234);
+		assert_eq!(first.note_count, Some(42));
+		assert_eq!(first.like_count, Some(30));
+		assert_eq!(first.reblog_count, Some(10));
+		assert_eq!(first.reply_count, Some(2));
+
+		let second = &page.posts[1];
+		assert_eq!(second.note_count, Some(0));
+		assert_eq!(second.like_count, None);
+		assert_eq!(second.reblog_count, None);
+		assert_eq!(second.reply_count, None);
+	}
+
+	#[test]
+	fn test_fetched_post_summary_and_slug() {
+		let post = from_str::<FetchedPost>(
+			r#"{
+	"id": 1234,
+	"blog_name": "staff",
+	"timestamp": 1654041600,
+	"summary": "Five years of working with this awesome girl",
+	"slug": "five-years-of-working-with-this-awesome-girl",
+	"content": [],
+	"layout": []
+}"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			post.summary.as_deref(),
+			Some("Five years of working with this awesome girl")
+		);
+		assert_eq!(
+			post.slug.as_deref(),
+			Some("five-years-of-working-with-this-awesome-girl")
+		);
+	}
+
+	#[test]
+	fn test_text_format_link() {
+		let link = TextFormatType::link(s!("https://www.tumblr.com/")).unwrap();
+		assert_eq!(link.url(), Some("https://www.tumblr.com/"));
+		assert_eq!(TextFormatType::Bold.url(), None);
+
+		assert!(matches!(
+			TextFormatType::link(s!("javascript:alert(1)")),
+			Err(Error::Validation(_))
+		));
+		assert!(matches!(
+			TextFormatType::link(s!("not a url")),
+			Err(Error::Validation(_))
+		));
+	}
+
+	#[test]
+	fn test_sort_by_timestamp() {
+		let post = |id: u64, timestamp: u64| {
+			from_str::<FetchedPost>(&format!(
+				r#"{{"id": {id}, "blog_name": "staff", "timestamp": {timestamp}}}"#
+			))
+			.unwrap()
+		};
+
+		let mut posts = [post(1, 300), post(2, 100), post(3, 200)];
+		posts.sort_by(FetchedPost::by_timestamp);
+
+		assert_eq!(
+			posts.iter().map(|post| post.id).collect::<Vec<_>>(),
+			vec![2, 3, 1]
+		);
+	}
+
+	#[test]
+	fn test_queued_posts() {
+		let mut page = from_str::<PostsPage>(
+			r#"{
+	"posts": [
+		{
+			"id": 1,
+			"blog_name": "staff",
+			"timestamp": 1654041600,
+			"state": "queued",
+			"scheduled_publish_time": 1654128000
+		},
+		{
+			"id": 2,
+			"blog_name": "staff",
+			"timestamp": 1654041700,
+			"state": "queued",
+			"scheduled_publish_time": 1654084800
+		}
+	]
+}"#,
+		)
+		.unwrap();
+
+		assert_eq!(page.posts[0].scheduled_publish_time, Some(1654128000));
+		assert_eq!(page.posts[1].scheduled_publish_time, Some(1654084800));
+
+		page.posts.sort_by(FetchedPost::by_scheduled_publish_time);
+		assert_eq!(page.iter().map(|post| post.id).collect::<Vec<_>>(), vec![2, 1]);
+	}
+
+	#[test]
+	fn test_trail_modes() {
+		let full = from_str::<ReblogTrail>(
+			r#"{
+	"post": { "id": 1 },
+	"blog": { "uuid": "t:root", "name": "root" },
+	"content": [{ "type": "text", "text": "root post" }],
+	"layout": []
+}"#,
+		)
+		.unwrap();
+
+		assert!(matches!(
+			full,
+			ReblogTrail::Ok { post, content, .. } if post.id == 1 && content.len() == 1
+		));
+
+		let compact = from_str::<ReblogTrail>(
+			r#"{
+	"post": { "id": 1 },
+	"blog": { "uuid": "t:root", "name": "root" }
+}"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			compact,
+			ReblogTrail::Reference {
+				post: PostInfo { id: 1 },
+				blog: BlogInfo {
+					uuid: s!("t:root"),
+					name: Some(s!("root")),
+				},
+			}
+		);
+	}
+
+	#[test]
+	fn test_validate_layout() {
+		let post = |layout: serde_json::Value| Post {
+			content: serde_json::from_value(serde_json::json!([
+				{ "type": "text", "text": "one" },
+				{ "type": "text", "text": "two" },
+			]))
+			.unwrap(),
+			layout: Some(serde_json::from_value(layout).unwrap()),
+			..Default::default()
+		};
+
+		assert!(post(serde_json::json!([
+			{ "type": "rows", "blocks": [{ "blocks": [0, 1] }] },
+			{ "type": "condensed", "blocks": [0, 1] },
+			{ "type": "ask", "blocks": [0] },
+		]))
+		.validate()
+		.is_ok());
+
+		let message = |post: Post| match post.validate() {
+			Err(Error::Validation(message)) => message,
+			other => panic!("expected a validation error, got {other:?}"),
+		};
+
+		assert_eq!(
+			message(post(serde_json::json!([
+				{ "type": "rows", "blocks": [{ "blocks": [0] }, { "blocks": [2] }] },
+			]))),
+			"layout refers to block 2, but the post only has 2 blocks"
+		);
+		assert!(message(post(serde_json::json!([{ "type": "ask", "blocks": [5] }]))).contains("5"));
+		assert_eq!(
+			message(post(serde_json::json!([{ "type": "condensed", "blocks": [1] }]))),
+			"condensed layout must list blocks 0, 1, 2, ... in order, but has 1 at position 0"
+		);
+		assert!(post(serde_json::json!([{ "type": "condensed", "blocks": [0, 1, 2] }]))
+			.validate()
+			.is_err());
+	}
+
+	#[test]
+	fn test_split_text_block() {
+		let format = |start, end, format_type| TextFormatting {
+			start,
+			end,
+			format_type,
+		};
+
+		// "🐶" is one char but four bytes
+		let block = ContentBlock::Text {
+			text: s!("🐶 corgis 🐶 rule"),
+			subtype: Some(TextSubtype::Quote),
+			indent_level: None,
+			formatting: Some(vec![
+				format(0, 8, TextFormatType::Bold),
+				format(2, 15, TextFormatType::Italic),
+				format(11, 15, TextFormatType::Small),
+			]),
+		};
+
+		assert_eq!(
+			block.split_text_block(9),
+			vec![
+				ContentBlock::Text {
+					text: s!("🐶 corgis "),
+					subtype: Some(TextSubtype::Quote),
+					indent_level: None,
+					formatting: Some(vec![
+						format(0, 8, TextFormatType::Bold),
+						format(2, 9, TextFormatType::Italic),
+					]),
+				},
+				ContentBlock::Text {
+					text: s!("🐶 rule"),
+					subtype: Some(TextSubtype::Quote),
+					indent_level: None,
+					formatting: Some(vec![
+						format(0, 6, TextFormatType::Italic),
+						format(2, 6, TextFormatType::Small),
+					]),
+				},
+			]
+		);
+
+		let short = ContentBlock::Text {
+			text: s!("short"),
+			subtype: None,
+			indent_level: None,
+			formatting: None,
+		};
+		assert_eq!(short.split_text_block(0).len(), 5);
+
+		let link = || ContentBlock::link(s!("https://www.tumblr.com/"), None, None, None).unwrap();
+		assert_eq!(link().split_text_block(1), vec![link()]);
+	}
+
+	#[test]
+	fn test_post_stats() {
+		let post = Post {
+			content: serde_json::from_value(serde_json::json!([
+				{ "type": "text", "text": "🐶 corgi" },
+				{ "type": "image", "media": [{ "url": "https://64.media.tumblr.com/corgi.jpg" }] },
+				{ "type": "text", "text": "über" },
+				{ "type": "video", "source": { "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ" } },
+				{ "type": "image", "media": [{ "url": "https://64.media.tumblr.com/corgi.gif" }] },
+			]))
+			.unwrap(),
+			tags: Some(vec!["corgis".into(), "dogs".into()]),
+			..Default::default()
+		};
+
+		assert_eq!(
+			post.stats(),
+			PostStats {
+				block_count: 5,
+				text_char_count: 11,
+				image_count: 2,
+				has_video: true,
+				tag_count: 2,
+			}
+		);
+		assert_eq!(Post::default().stats(), PostStats::default());
+	}
+
+	#[test]
+	fn test_condensed_layout() {
+		assert_serde(
+			r#"{
+  "type": "condensed",
+  "truncate_after": 1
+}"#,
+			LayoutBlock::Condensed {
+				truncate_after: Some(1),
+				blocks: None,
+			},
+		);
+
+		assert_serde(
+			r#"{
+  "type": "condensed",
+  "blocks": [
+    0,
+    1
+  ]
+}"#,
+			LayoutBlock::Condensed {
+				truncate_after: None,
+				blocks: Some(vec![0, 1]),
+			},
+		);
+
+		let post = |truncate_after, blocks| Post {
+			content: vec![
+				ContentBlock::Text {
+					text: s!("one"),
+					subtype: None,
+					indent_level: None,
+					formatting: None,
+				},
+				ContentBlock::Text {
+					text: s!("two"),
+					subtype: None,
+					indent_level: None,
+					formatting: None,
+				},
+			],
+			layout: Some(vec![LayoutBlock::Condensed {
+				truncate_after,
+				blocks,
+			}]),
+			..Default::default()
+		};
+
+		assert!(post(Some(0), None).validate().is_ok());
+		assert!(post(None, Some(vec![0])).validate().is_ok());
+		assert!(post(None, Some(vec![0, 1])).to_body().is_ok());
+		assert!(matches!(
+			post(None, None).validate(),
+			Err(Error::Validation(_))
+		));
+		assert!(matches!(
+			post(None, Some(vec![0, 0])).to_body(),
+			Err(Error::Validation(_))
+		));
+		assert!(matches!(
+			post(Some(0), Some(vec![1])).to_body(),
+			Err(Error::Validation(_))
+		));
+	}
+
+	#[test]
+	fn test_answer_ask() {
+		let text = |text: &str| ContentBlock::Text {
+			text: text.to_owned(),
+			subtype: None,
+			indent_level: None,
+			formatting: None,
+		};
+
+		let body = Post::answer_ask(
+			vec![text("what's your favorite color?")],
+			Some(BlogInfo {
+				uuid: s!("t:asker"),
+				name: None,
+			}),
+			vec![text("blue"), text("no, yellow")],
+		)
+		.to_body()
+		.unwrap();
+
+		assert_eq!(
+			body,
+			serde_json::json!({
+				"content": [
+					{ "type": "text", "text": "what's your favorite color?" },
+					{ "type": "text", "text": "blue" },
+					{ "type": "text", "text": "no, yellow" },
+				],
+				"layout": [{
+					"type": "ask",
+					"blocks": [0],
+					"attribution": { "type": "blog", "blog": { "uuid": "t:asker" } },
+				}],
+			})
+		);
+
+		let body = Post {
+			is_private: Some(true),
+			..Post::answer_ask(vec![text("secret?")], None, vec![text("yes")])
+		}
+		.to_body()
+		.unwrap();
+
+		assert_eq!(body["is_private"], true);
+		assert_eq!(body["layout"][0]["type"], "ask");
+
+		assert!(matches!(
+			Post {
+				content: vec![text("not an answer")],
+				is_private: Some(true),
+				..Default::default()
+			}
+			.to_body(),
+			Err(Error::Validation(_))
+		));
+
+		assert_eq!(
+			Post::answer_ask(vec![text("anon?")], None, vec![]).layout,
+			Some(vec![LayoutBlock::Ask {
+				blocks: vec![0],
+				attribution: None,
+			}])
+		);
+	}
+
+	#[test]
+	fn test_link_display_url_retrieve_only() {
+		let link: ContentBlock = from_str(
+			r#"{
+	"type": "link",
+	"url": "https://www.nytimes.com/2017/06/15/us/politics/secrecy-surveillance-images.html",
+	"display_url": "nytimes.com/2017/06/15/us/politics/secrecy-surveillance-images.html"
+}"#,
+		)
+		.unwrap();
+
+		assert!(matches!(
+			&link,
+			ContentBlock::Link {
+				display_url: Some(display_url),
+				..
+			} if display_url == "nytimes.com/2017/06/15/us/politics/secrecy-surveillance-images.html"
+		));
+
+		let body = Post {
+			content: vec![link],
+			..Default::default()
+		}
+		.to_body()
+		.unwrap();
+
+		assert_eq!(
+			body,
+			serde_json::json!({
+				"content": [{
+					"type": "link",
+					"url": "https://www.nytimes.com/2017/06/15/us/politics/secrecy-surveillance-images.html",
+				}],
+			})
+		);
+	}
+
+	#[test]
+	fn test_lenient_posts_page() {
+		let page = from_str::<LenientPostsPage>(
+			r#"{
+	"posts": [
+		{
+			"id": 1,
+			"blog_name": "staff",
+			"timestamp": 1654041600,
+			"content": [{ "type": "text", "text": "first" }]
+		},
+		{
+			"id": 2,
+			"blog_name": "staff",
+			"timestamp": 1654041700,
+			"content": [{ "type": "hologram", "url": "https://example.com" }]
+		},
+		{
+			"id": 3,
+			"blog_name": "staff",
+			"timestamp": 1654041800
+		}
+	],
+	"total_posts": 3
+}"#,
+		)
+		.unwrap();
+
+		assert_eq!(page.total_posts, Some(3));
+		assert_eq!(page.posts.len(), 3);
+		assert_eq!(page.posts[0].as_ref().unwrap().id, 1);
+		assert_eq!(page.posts[2].as_ref().unwrap().id, 3);
+
+		let err = page.posts[1].as_ref().unwrap_err();
+		assert_eq!(err.id, Some(2));
+		assert_eq!(err.raw["content"][0]["type"], "hologram");
+
+		assert!(from_str::<PostsPage>(
+			r#"{"posts": [{ "id": 2, "blog_name": "staff", "timestamp": 0, "content": [{ "type": "hologram" }] }]}"#
+		)
+		.is_err());
+	}
+
+	#[test]
+	fn test_text_subtype_rendering() {
+		assert_eq!(TextSubtype::OrderedListItem.list_marker(0).as_deref(), Some("1."));
+		assert_eq!(TextSubtype::OrderedListItem.list_marker(9).as_deref(), Some("10."));
+		assert_eq!(TextSubtype::UnorderedListItem.list_marker(3).as_deref(), Some("•"));
+		assert_eq!(TextSubtype::Quote.list_marker(0), None);
+
+		assert_eq!(TextSubtype::OrderedListItem.list_tag(), Some("ol"));
+		assert_eq!(TextSubtype::UnorderedListItem.list_tag(), Some("ul"));
+		assert_eq!(TextSubtype::Heading1.list_tag(), None);
+		assert!(TextSubtype::UnorderedListItem.is_list_item());
+		assert!(!TextSubtype::Indented.is_list_item());
+
+		assert_eq!(TextSubtype::Heading1.html_tag(), "h1");
+		assert_eq!(TextSubtype::Heading2.html_tag(), "h2");
+		assert_eq!(TextSubtype::OrderedListItem.html_tag(), "li");
+		assert_eq!(TextSubtype::Indented.html_tag(), "blockquote");
+		assert!(TextSubtype::Heading2.is_heading());
+		assert!(!TextSubtype::Quirky.is_heading());
+	}
+
+	#[test]
+	fn test_read_more_after() {
+		let text = |text: &str| ContentBlock::Text {
+			text: text.to_owned(),
+			subtype: None,
+			indent_level: None,
+			formatting: None,
+		};
+
+		let body = Post {
+			content: vec![text("above"), text("below")],
+			..Default::default()
+		}
+		.read_more_after(0)
+		.unwrap()
+		.to_body()
+		.unwrap();
+
+		assert_eq!(
+			body["layout"],
+			serde_json::json!([{
+				"type": "rows",
+				"blocks": [{ "blocks": [0] }, { "blocks": [1] }],
+				"truncate_after": 0,
+			}])
+		);
+
+		assert!(matches!(
+			Post {
+				content: vec![text("only")],
+				..Default::default()
+			}
+			.read_more_after(0),
+			Err(Error::Validation(_))
+		));
+	}
+
+	#[test]
+	fn test_slug() {
+		let post = |slug: &str| Post {
+			slug: Some(slug.to_owned()),
+			..Default::default()
+		};
+
+		assert_eq!(
+			post("five-years-in-2022").to_body().unwrap(),
+			serde_json::json!({
+				"content": [],
+				"slug": "five-years-in-2022",
+			})
+		);
+
+		for invalid in ["", "Five-Years", "five years", "five_years", "café"] {
+			assert!(matches!(post(invalid).to_body(), Err(Error::Validation(_))));
+		}
+	}
+
+	#[test]
+	fn test_app_attribution() {
+		let attribution = Attribution::app(
+			s!("https://www.instagram.com/p/BVZyxTklQWX/"),
+			None,
+			Some(s!("tibbythecorgi - Very Cute")),
+			None,
+		)
+		.unwrap();
+
+		assert_eq!(attribution.app_name(), Some("instagram.com"));
+		assert_eq!(attribution.display_text(), Some("tibbythecorgi - Very Cute"));
+		assert_eq!(attribution.logo(), None);
+
+		let attribution = Attribution::app(
+			s!("https://www.instagram.com/p/BVZyxTklQWX/"),
+			Some(s!("Instagram")),
+			None,
+			None,
+		)
+		.unwrap();
+
+		assert_eq!(attribution.app_name(), Some("Instagram"));
+		assert_eq!(
+			Attribution::Link {
+				url: s!("http://shahkashani.com")
+			}
+			.app_name(),
+			None
+		);
+		assert!(matches!(
+			Attribution::app(s!("not a url"), None, None, None),
+			Err(Error::Validation(_))
+		));
+	}
+
+	#[test]
+	fn test_fetched_answer() {
+		let post = from_str::<FetchedPost>(
+			r#"{
+	"id": 1,
+	"blog_name": "staff",
+	"timestamp": 1654041600,
+	"original_type": "note",
+	"asking_name": "asker",
+	"asking_url": "https://asker.tumblr.com/",
+	"is_anonymous": false
+}"#,
+		)
+		.unwrap();
+
+		assert_eq!(post.original_type.as_deref(), Some("note"));
+		assert_eq!(post.asking_name.as_deref(), Some("asker"));
+		assert_eq!(post.asking_url.as_deref(), Some("https://asker.tumblr.com/"));
+		assert_eq!(post.is_anonymous, Some(false));
+		assert!(post.extra.is_empty());
+
+		let post = from_str::<FetchedPost>(
+			r#"{
+	"id": 2,
+	"blog_name": "staff",
+	"timestamp": 1654041600,
+	"original_type": "note",
+	"is_anonymous": true
+}"#,
+		)
+		.unwrap();
+
+		assert_eq!(post.asking_name, None);
+		assert_eq!(post.asking_url, None);
+		assert_eq!(post.is_anonymous, Some(true));
+	}
+
+	#[test]
+	fn test_can_reblog() {
+		let post = |fields: &str| {
+			from_str::<FetchedPost>(&format!(
+				r#"{{"id": 1, "blog_name": "staff", "timestamp": 1654041600{fields}}}"#
+			))
+			.unwrap()
+		};
+
+		assert!(post(r#", "reblog_key": "abc""#).can_reblog());
+		assert!(post(
+			r#", "reblog_key": "abc", "can_reblog": true, "interactability_reblog": "everyone""#
+		)
+		.can_reblog());
+		assert!(!post("").can_reblog());
+		assert!(!post(r#", "reblog_key": "abc", "can_reblog": false"#).can_reblog());
+
+		let noone = post(r#", "reblog_key": "abc", "interactability_reblog": "noone""#);
+		assert_eq!(noone.interactability_reblog, Some(Interactability::Noone));
+		assert!(!noone.can_reblog());
+
+		let mutuals = post(r#", "interactability_reblog": "mutuals""#);
+		assert_eq!(
+			mutuals.interactability_reblog,
+			Some(Interactability::Other("mutuals".to_owned()))
+		);
+		assert_eq!(
+			serde_json::to_value(&mutuals).unwrap()["interactability_reblog"],
+			"mutuals"
+		);
+	}
+
+	#[test]
+	fn test_note_unknown_type() {
+		assert_serde(
+			r#"{
+  "type": "tip",
+  "timestamp": 1654041600,
+  "blog_name": "staff"
+}"#,
+			Note {
+				note_type: NoteType::Other(s!("tip")),
+				timestamp: 1654041600,
+				blog_name: s!("staff"),
+				blog_uuid: None,
+				blog_url: None,
+				post_id: None,
+				reply_text: None,
+			},
+		);
+	}
+
+	#[test]
+	fn test_fetched_post_extra_fields() {
+		let post = from_str::<FetchedPost>(
+			r#"{
+	"id": 1,
+	"blog_name": "staff",
+	"timestamp": 1654041600,
+	"reblogged_from_name": "parent",
+	"liked": false,
+	"is_blaze": true,
+	"interactability_reblog": "everyone"
+}"#,
+		)
+		.unwrap();
+
+		assert_eq!(post.extra["is_blaze"], serde_json::json!(true));
+		assert_eq!(post.extra.len(), 1);
+		assert_eq!(post.interactability_reblog, Some(Interactability::Everyone));
+		assert_eq!(post.liked, Some(false));
+		assert_eq!(
+			post.reblog_info.reblogged_from_name.as_deref(),
+			Some("parent")
+		);
+	}
+
+	#[test]
+	fn test_post_options_merge() {
+		let body = Post {
+			exclude_from_search: Some(true),
+			post_options: Some(
+				[
+					(s!("interactability_blaze"), serde_json::json!("everyone")),
+					(s!("exclude_from_search"), serde_json::json!(false)),
+				]
+				.into(),
+			),
+			..Default::default()
+		}
+		.to_body()
+		.unwrap();
+
+		assert_eq!(
+			body,
+			serde_json::json!({
+				"content": [],
+				"exclude_from_search": true,
+				"interactability_blaze": "everyone",
+			})
 		);
 	}
 }
@@ -0,0 +1,79 @@
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+/// How requests answered with `429 Too Many Requests` are retried, see [`ClientBuilder::retry_rate_limited`](crate::ClientBuilder::retry_rate_limited)
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct RetryPolicy {
+	pub(crate) max_retries: u32,
+	pub(crate) base_backoff: Duration,
+	pub(crate) max_delay: Duration,
+}
+
+impl RetryPolicy {
+	/// How long to wait before retry number `attempt` (from 0): what `Retry-After` asks for, or else `base_backoff` doubled `attempt` times.
+	/// `None` if that's longer than `max_delay`, so the caller gives up instead, e.g. on a daily quota
+	pub(crate) fn delay(&self, attempt: u32, headers: &HeaderMap) -> Option<Duration> {
+		let delay = retry_after(headers, SystemTime::now()).unwrap_or_else(|| {
+			self.base_backoff
+				.saturating_mul(2u32.saturating_pow(attempt))
+		});
+
+		Some(delay).filter(|delay| *delay <= self.max_delay)
+	}
+}
+
+/// The `Retry-After` header as a delay from `now`, in either its seconds or HTTP-date form.
+/// A date in the past is no delay
+fn retry_after(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+	let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+	if let Ok(seconds) = value.parse() {
+		return Some(Duration::from_secs(seconds));
+	}
+
+	let date = httpdate::parse_http_date(value).ok()?;
+
+	Some(date.duration_since(now).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_retry_after() {
+		let headers = |value: &str| HeaderMap::from_iter([(RETRY_AFTER, value.parse().unwrap())]);
+
+		// Wed, 01 Jun 2022 00:00:00 GMT
+		let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1654041600);
+
+		assert_eq!(
+			retry_after(&headers("120"), now),
+			Some(Duration::from_secs(120))
+		);
+		assert_eq!(
+			retry_after(&headers("Wed, 01 Jun 2022 00:00:30 GMT"), now),
+			Some(Duration::from_secs(30))
+		);
+		assert_eq!(
+			retry_after(&headers("Tue, 31 May 2022 23:59:00 GMT"), now),
+			Some(Duration::ZERO)
+		);
+		assert_eq!(retry_after(&headers("soon"), now), None);
+		assert_eq!(retry_after(&HeaderMap::new(), now), None);
+
+		let policy = RetryPolicy {
+			max_retries: 3,
+			base_backoff: Duration::from_millis(100),
+			max_delay: Duration::from_secs(60),
+		};
+		assert_eq!(
+			policy.delay(2, &HeaderMap::new()),
+			Some(Duration::from_millis(400))
+		);
+		assert_eq!(policy.delay(2, &headers("1")), Some(Duration::from_secs(1)));
+		assert_eq!(policy.delay(0, &headers("86400")), None);
+		assert_eq!(policy.delay(10, &HeaderMap::new()), None);
+	}
+}
@@ -0,0 +1,81 @@
+//! Sanitizing `embed_html` from [`ContentBlock::Audio`](crate::post::ContentBlock::Audio) and [`ContentBlock::Video`](crate::post::ContentBlock::Video) before rendering it
+
+use std::collections::HashSet;
+
+use reqwest::Url;
+
+/// Hosts whose `<iframe>` embeds are kept by [`sanitize_embed_html`]
+pub const ALLOWED_EMBED_HOSTS: &[&str] = &[
+	"www.youtube.com",
+	"www.youtube-nocookie.com",
+	"player.vimeo.com",
+	"w.soundcloud.com",
+	"open.spotify.com",
+	"bandcamp.com",
+	"embed.tumblr.com",
+];
+
+fn is_allowed_embed(src: &str) -> bool {
+	match src.parse::<Url>() {
+		Ok(url) if url.scheme() == "https" => url
+			.host_str()
+			.is_some_and(|host| ALLOWED_EMBED_HOSTS.contains(&host)),
+		_ => false,
+	}
+}
+
+/// Strips scripts, event handler attributes and other dangerous markup from `html`.
+/// `<iframe>`s are kept only if their `src` is an `https` URL on one of [`ALLOWED_EMBED_HOSTS`]; other iframes lose their `src`
+pub fn sanitize_embed_html(html: &str) -> String {
+	ammonia::Builder::default()
+		.add_tags(["iframe", "video", "audio", "source"])
+		.add_tag_attributes(
+			"iframe",
+			[
+				"src",
+				"width",
+				"height",
+				"frameborder",
+				"allow",
+				"allowfullscreen",
+			],
+		)
+		.add_tag_attributes("video", ["src", "width", "height", "poster", "controls"])
+		.add_tag_attributes("audio", ["src", "controls"])
+		.add_tag_attributes("source", ["src", "type"])
+		.attribute_filter(|element, attribute, value| match (element, attribute) {
+			("iframe", "src") if !is_allowed_embed(value) => None,
+			_ => Some(value.into()),
+		})
+		.clean_content_tags(HashSet::from(["script", "style"]))
+		.clean(html)
+		.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_strips_scripts_and_handlers() {
+		let clean = sanitize_embed_html(
+			r#"<script>alert(document.cookie)</script><iframe src="https://www.youtube.com/embed/abc" onload="alert(1)" width="540" height="304"></iframe>"#,
+		);
+
+		assert!(!clean.contains("script"));
+		assert!(!clean.contains("alert"));
+		assert!(!clean.contains("onload"));
+		assert!(clean.contains(r#"src="https://www.youtube.com/embed/abc""#));
+		assert!(clean.contains(r#"width="540""#));
+	}
+
+	#[test]
+	fn test_drops_disallowed_iframe_src() {
+		let clean = sanitize_embed_html(
+			r#"<iframe src="https://evil.example.com/embed"></iframe><iframe src="javascript:alert(1)"></iframe>"#,
+		);
+
+		assert!(!clean.contains("evil.example.com"));
+		assert!(!clean.contains("javascript"));
+	}
+}
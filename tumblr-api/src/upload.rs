@@ -0,0 +1,572 @@
+//! Uploading media files along with a post, as `multipart/form-data`
+
+use std::{collections::HashSet, path::Path};
+
+use rand::{distributions::Alphanumeric, Rng};
+use reqwest::{header::CONTENT_TYPE, Method, Url};
+use serde::de::DeserializeOwned;
+
+use crate::{
+	blog::BlogIdentifier, oauth, parse_response, post::CreatedPost, post::Post, Authenticated,
+	Client, Error,
+};
+
+/// A file to upload with a post.  Content blocks refer to it by `identifier`, e.g. through [`Media::uploaded`](crate::post::Media::uploaded)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaFile {
+	identifier: String,
+	mime_type: String,
+	data: Vec<u8>,
+}
+
+impl MediaFile {
+	/// Returns [`Error::Validation`] if `identifier` is empty, is `json`, which holds the post itself,
+	/// or has quotes or control characters, or if `mime_type` isn't a media type.
+	/// Both end up in the part's headers, so they mustn't be able to break out of them
+	pub fn new(identifier: String, mime_type: String, data: Vec<u8>) -> crate::Result<Self> {
+		if identifier.is_empty()
+			|| identifier == "json"
+			|| identifier.chars().any(|ch| ch == '"' || ch.is_control())
+		{
+			return Err(Error::Validation(format!(
+				"{identifier:?} can't be used as a media identifier"
+			)));
+		}
+
+		if mime_type.parse::<mime::Mime>().is_err() {
+			return Err(Error::Validation(format!(
+				"{mime_type:?} isn't a media type"
+			)));
+		}
+
+		Ok(Self {
+			identifier,
+			mime_type,
+			data,
+		})
+	}
+
+	/// Reads the file at `path`, guessing its type from the extension.
+	/// Returns [`Error::Validation`] for extensions other than common image and video types
+	pub async fn read(identifier: String, path: impl AsRef<Path>) -> crate::Result<Self> {
+		let path = path.as_ref();
+
+		let extension = path
+			.extension()
+			.and_then(|extension| extension.to_str())
+			.map(str::to_ascii_lowercase);
+
+		let mime_type = match extension.as_deref() {
+			Some("jpg" | "jpeg") => "image/jpeg",
+			Some("png") => "image/png",
+			Some("gif") => "image/gif",
+			Some("webp") => "image/webp",
+			Some("mp4") => "video/mp4",
+			Some("mov") => "video/quicktime",
+			Some("webm") => "video/webm",
+			_ => {
+				return Err(Error::Validation(format!(
+					"can't tell the media type of {}",
+					path.display()
+				)))
+			}
+		};
+
+		Self::new(
+			identifier,
+			mime_type.to_owned(),
+			tokio::fs::read(path).await?,
+		)
+	}
+
+	#[inline]
+	pub fn identifier(&self) -> &str {
+		&self.identifier
+	}
+
+	#[inline]
+	pub fn mime_type(&self) -> &str {
+		&self.mime_type
+	}
+
+	#[inline]
+	pub fn data(&self) -> &[u8] {
+		&self.data
+	}
+
+	/// The same file under another `identifier`, for endpoints that expect it as a specific part
+	pub(crate) fn with_identifier(self, identifier: &str) -> Self {
+		Self {
+			identifier: identifier.to_owned(),
+			..self
+		}
+	}
+}
+
+/// A `multipart/form-data` body with `json` (if any) as the `json` part, followed by each of `files`.
+/// Returns the body and its boundary
+fn multipart_body(
+	json: Option<&serde_json::Value>,
+	files: &[MediaFile],
+) -> crate::Result<(Vec<u8>, String)> {
+	let boundary: String = rand::thread_rng()
+		.sample_iter(&Alphanumeric)
+		.take(32)
+		.map(char::from)
+		.collect();
+
+	let mut body = Vec::new();
+
+	if let Some(json) = json {
+		body.extend_from_slice(
+			format!(
+				"--{boundary}\r\nContent-Disposition: form-data; name=\"json\"\r\nContent-Type: application/json\r\n\r\n"
+			)
+			.as_bytes(),
+		);
+		body.extend_from_slice(&serde_json::to_vec(json).map_err(Error::Serialize)?);
+		body.extend_from_slice(b"\r\n");
+	}
+
+	for file in files {
+		body.extend_from_slice(
+			format!(
+				"--{boundary}\r\nContent-Disposition: form-data; name=\"{0}\"; filename=\"{0}\"\r\nContent-Type: {1}\r\n\r\n",
+				file.identifier, file.mime_type
+			)
+			.as_bytes(),
+		);
+		body.extend_from_slice(&file.data);
+		body.extend_from_slice(b"\r\n");
+	}
+
+	body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+	Ok((body, boundary))
+}
+
+/// Every `identifier` the post's blocks refer to, e.g. through [`Media::uploaded`](crate::post::Media::uploaded)
+fn referenced_identifiers<'a>(value: &'a serde_json::Value, identifiers: &mut HashSet<&'a str>) {
+	match value {
+		serde_json::Value::Object(object) => {
+			if let Some(serde_json::Value::String(identifier)) = object.get("identifier") {
+				identifiers.insert(identifier);
+			}
+			object
+				.values()
+				.for_each(|value| referenced_identifiers(value, identifiers));
+		}
+		serde_json::Value::Array(values) => values
+			.iter()
+			.for_each(|value| referenced_identifiers(value, identifiers)),
+		_ => (),
+	}
+}
+
+impl Client<Authenticated> {
+	/// Like [`Client::create_post`], but uploads `files` in the same request.
+	/// Returns [`Error::Validation`] if two files have the same identifier, or a block refers to a file that isn't in `files`
+	pub async fn create_post_with_media(
+		&self,
+		blog_name: &str,
+		post: Post,
+		files: &[MediaFile],
+	) -> crate::Result<CreatedPost> {
+		let blog_identifier = BlogIdentifier::parse(blog_name).to_path_segment();
+
+		self.send_with_media(
+			Method::POST,
+			&format!("blog/{blog_identifier}/posts"),
+			post,
+			files,
+		)
+		.await
+	}
+
+	/// Replaces post `post_id` with `post`, uploading only `files`.
+	/// Media that's already hosted can be kept by referring to its URL (see [`Media::new`](crate::post::Media::new)),
+	/// so swapping one image doesn't re-upload the others.
+	/// Returns [`Error::Validation`] as [`Client::create_post_with_media`] does
+	pub async fn edit_post_with_media(
+		&self,
+		blog_name: &str,
+		post_id: u64,
+		post: Post,
+		files: &[MediaFile],
+	) -> crate::Result<CreatedPost> {
+		let blog_identifier = BlogIdentifier::parse(blog_name).to_path_segment();
+
+		self.send_with_media(
+			Method::PUT,
+			&format!("blog/{blog_identifier}/posts/{post_id}"),
+			post,
+			files,
+		)
+		.await
+	}
+
+	async fn send_with_media(
+		&self,
+		method: Method,
+		path: &str,
+		post: Post,
+		files: &[MediaFile],
+	) -> crate::Result<CreatedPost> {
+		let mut identifiers = HashSet::new();
+		if let Some(file) = files
+			.iter()
+			.find(|file| !identifiers.insert(file.identifier.as_str()))
+		{
+			return Err(Error::Validation(format!(
+				"more than one file has the identifier {:?}",
+				file.identifier
+			)));
+		}
+
+		let json = post.to_body()?;
+
+		let mut referenced = HashSet::new();
+		referenced_identifiers(&json, &mut referenced);
+		if let Some(missing) = referenced.difference(&identifiers).next() {
+			return Err(Error::Validation(format!(
+				"the post refers to {missing:?}, but no file has that identifier"
+			)));
+		}
+
+		self.send_multipart(method, path, Some(&json), files).await
+	}
+
+	/// Sends `json` and `files` to `path` as `multipart/form-data`.
+	/// The parts aren't form parameters, so they're left out of the OAuth signature
+	pub(crate) async fn send_multipart<T: DeserializeOwned>(
+		&self,
+		method: Method,
+		path: &str,
+		json: Option<&serde_json::Value>,
+		files: &[MediaFile],
+	) -> crate::Result<T> {
+		let url: Url = format!("{}/{path}", self.api_base).parse()?;
+
+		let (body, boundary) = multipart_body(json, files)?;
+
+		let req = self
+			.client
+			.request(method, url)
+			.header(
+				CONTENT_TYPE,
+				format!("multipart/form-data; boundary={boundary}"),
+			)
+			.body(body)
+			.build()?;
+
+		let credentials = &self.state().0;
+
+		let res = oauth::ResignableRequest::new(self, req)
+			.sign_and_send(
+				Some(&credentials.oauth_token),
+				Some(&credentials.oauth_token_secret),
+				None,
+			)
+			.await?;
+
+		parse_response(&res.text().await?).map(|(_, response)| response)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use wiremock::{
+		matchers::{header_exists, method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	use super::*;
+	use crate::{
+		client::tests::{mock_client, ok_response},
+		post::{ContentBlock, Media},
+	};
+
+	#[tokio::test]
+	async fn test_video_with_poster() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("POST"))
+			.and(path("/blog/staff/posts"))
+			.and(header_exists("authorization"))
+			.respond_with(ResponseTemplate::new(201).set_body_raw(
+				r#"{"meta":{"status":201,"msg":"Created"},"response":{"id":"1234"}}"#,
+				"application/json",
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let video = MediaFile::new(
+			"video".to_owned(),
+			"video/mp4".to_owned(),
+			b"not really an mp4".to_vec(),
+		)
+		.unwrap();
+		let poster = MediaFile::new(
+			"poster".to_owned(),
+			"image/jpeg".to_owned(),
+			b"not really a jpeg".to_vec(),
+		)
+		.unwrap();
+
+		let post = Post {
+			content: vec![ContentBlock::uploaded_video(&video, Some(&poster)).unwrap()],
+			..Default::default()
+		};
+
+		let created = mock_client(&server)
+			.create_post_with_media("staff", post, &[video, poster])
+			.await
+			.unwrap();
+		assert_eq!(created.id, 1234);
+
+		let request = &server.received_requests().await.unwrap()[0];
+
+		let boundary = request
+			.headers
+			.iter()
+			.find(|(name, _)| name.as_str() == "content-type")
+			.map(|(_, values)| values.as_str())
+			.unwrap()
+			.strip_prefix("multipart/form-data; boundary=")
+			.unwrap()
+			.to_owned();
+
+		let body = String::from_utf8(request.body.clone()).unwrap();
+		let parts: Vec<_> = body
+			.split(&format!("--{boundary}"))
+			.filter_map(|part| part.strip_prefix("\r\n"))
+			.map(|part| {
+				part.trim_end_matches("\r\n")
+					.split_once("\r\n\r\n")
+					.unwrap()
+			})
+			.collect();
+
+		assert_eq!(parts.len(), 3);
+
+		assert!(parts[0].0.contains(r#"name="json""#));
+		assert_eq!(
+			serde_json::from_str::<serde_json::Value>(parts[0].1).unwrap(),
+			serde_json::json!({
+				"content": [{
+					"type": "video",
+					"source": { "media": { "type": "video/mp4", "identifier": "video" } },
+					"poster": { "type": "image/jpeg", "identifier": "poster" },
+				}],
+			})
+		);
+
+		assert!(parts[1].0.contains(r#"name="video""#));
+		assert!(parts[1].0.contains("Content-Type: video/mp4"));
+		assert_eq!(parts[1].1, "not really an mp4");
+
+		assert!(parts[2].0.contains(r#"name="poster""#));
+		assert!(parts[2].0.contains("Content-Type: image/jpeg"));
+		assert_eq!(parts[2].1, "not really a jpeg");
+	}
+
+	#[test]
+	fn test_validates_media_types() {
+		let file = |mime_type: &str| {
+			MediaFile::new(mime_type.replace('/', "-"), mime_type.to_owned(), vec![]).unwrap()
+		};
+
+		assert!(matches!(
+			ContentBlock::uploaded_video(&file("video/mp4"), Some(&file("video/mp4"))),
+			Err(Error::Validation(_))
+		));
+		assert!(matches!(
+			ContentBlock::uploaded_video(&file("image/png"), None),
+			Err(Error::Validation(_))
+		));
+		assert!(matches!(
+			ContentBlock::uploaded_video(&file("image/gif"), Some(&file("image/png"))),
+			Ok(ContentBlock::Image {
+				poster: Some(_),
+				..
+			})
+		));
+		assert!(MediaFile::new("json".to_owned(), "image/png".to_owned(), vec![]).is_err());
+	}
+
+	#[test]
+	fn test_rejects_header_injection() {
+		let file = |identifier: &str, mime_type: &str| {
+			MediaFile::new(identifier.to_owned(), mime_type.to_owned(), vec![])
+		};
+
+		assert!(file("image", "image/png; charset=binary").is_ok());
+
+		for identifier in ["a\"b", "a\r\nContent-Type: text/html", "a\0b"] {
+			assert!(
+				matches!(file(identifier, "image/png"), Err(Error::Validation(_))),
+				"accepted {identifier:?}"
+			);
+		}
+
+		for mime_type in [
+			"image/png\r\nX-Injected: yes",
+			"image/png; name=\"a\r\nb\"",
+			"png",
+			"",
+		] {
+			assert!(
+				matches!(file("image", mime_type), Err(Error::Validation(_))),
+				"accepted {mime_type:?}"
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_edit_keeps_hosted_media() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("PUT"))
+			.and(path("/blog/staff/posts/1234"))
+			.and(header_exists("authorization"))
+			.respond_with(ok_response(r#"{"id":"1234"}"#))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let replacement = MediaFile::new(
+			"replacement".to_owned(),
+			"image/png".to_owned(),
+			b"not really a png".to_vec(),
+		)
+		.unwrap();
+
+		let image = |media| ContentBlock::Image {
+			media: vec![media],
+			feedback_token: None,
+			poster: None,
+			attribution: None,
+			alt_text: None,
+			caption: None,
+		};
+
+		// the kept image is only referenced by URL, so the only file part is the replacement
+		let post = || Post {
+			content: vec![
+				image(Media::new(
+					"https://64.media.tumblr.com/kept.jpg".to_owned(),
+					None,
+					None,
+				)),
+				image(Media::uploaded(&replacement)),
+			],
+			..Default::default()
+		};
+
+		let client = mock_client(&server);
+
+		assert!(matches!(
+			client
+				.edit_post_with_media("staff", 1234, post(), &[])
+				.await,
+			Err(Error::Validation(_))
+		));
+
+		let edited = client
+			.edit_post_with_media("staff", 1234, post(), &[replacement])
+			.await
+			.unwrap();
+		assert_eq!(edited.id, 1234);
+
+		let request = &server.received_requests().await.unwrap()[0];
+		let body = String::from_utf8(request.body.clone()).unwrap();
+
+		let json = body
+			.split("\r\n\r\n")
+			.nth(1)
+			.and_then(|part| part.split("\r\n--").next())
+			.unwrap();
+		assert_eq!(
+			serde_json::from_str::<serde_json::Value>(json).unwrap(),
+			serde_json::json!({
+				"content": [
+					{ "type": "image", "media": [{ "url": "https://64.media.tumblr.com/kept.jpg" }] },
+					{ "type": "image", "media": [{ "type": "image/png", "identifier": "replacement" }] },
+				],
+			})
+		);
+
+		assert_eq!(body.matches("Content-Disposition").count(), 2);
+		assert!(body.contains(r#"name="replacement""#));
+		assert!(body.contains("not really a png"));
+	}
+
+	#[test]
+	fn test_multipart_signature() {
+		let client = crate::client::tests::authenticated(Client::builder(
+			"consumer-key".to_owned(),
+			"client-secret".to_owned(),
+		));
+		let url = "https://api.tumblr.com/v2/blog/staff/posts";
+
+		// the file parts aren't form parameters, so only the OAuth parameters are signed
+		let signature = |req: reqwest::Request| {
+			oauth::Request::new(&client, req)
+				.sign_at(
+					Some("token"),
+					Some("token-secret"),
+					None,
+					"1654041600",
+					"bm9uY2U=",
+				)
+				.unwrap()
+				.into_inner()
+				.headers()[reqwest::header::AUTHORIZATION]
+				.to_str()
+				.unwrap()
+				.split(',')
+				.find_map(|param| param.strip_prefix("oauth_signature="))
+				.unwrap()
+				.to_owned()
+		};
+
+		let image = |data: &[u8]| {
+			MediaFile::new("image".to_owned(), "image/png".to_owned(), data.to_vec()).unwrap()
+		};
+
+		let multipart = |file: MediaFile| {
+			let (body, boundary) =
+				multipart_body(Some(&serde_json::json!({ "content": [] })), &[file]).unwrap();
+
+			client
+				.client
+				.post(url)
+				.header(
+					CONTENT_TYPE,
+					format!("multipart/form-data; boundary={boundary}"),
+				)
+				.body(body)
+				.build()
+				.unwrap()
+		};
+
+		let form = reqwest::multipart::Form::new()
+			.text("json", r#"{"content":[]}"#)
+			.part(
+				"image",
+				reqwest::multipart::Part::bytes(b"a png".to_vec()).file_name("image"),
+			);
+
+		// computed independently over
+		// POST&https%3A%2F%2Fapi.tumblr.com%2Fv2%2Fblog%2Fstaff%2Fposts&oauth_consumer_key%3Dconsumer-key%26oauth_nonce%3Dbm9uY2U%253D%26oauth_signature_method%3DHMAC-SHA1%26oauth_timestamp%3D1654041600%26oauth_token%3Dtoken%26oauth_version%3D1.0
+		let reference = r#""adnHEIGHxpRaxKILONlJb2JsVz8%3D""#;
+
+		assert_eq!(signature(multipart(image(b"a png"))), reference);
+		assert_eq!(signature(multipart(image(b"another png"))), reference);
+		assert_eq!(
+			signature(client.client.post(url).multipart(form).build().unwrap()),
+			reference
+		);
+	}
+}
@@ -0,0 +1,533 @@
+use futures_util::{stream, Stream, TryStreamExt};
+use reqwest::{header::HeaderMap, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{check_limit, oauth, parse_response, with_query, Authenticated, Client, Error};
+
+/// Unread counts for the user's badges, see [`Client::counts`]
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Counts {
+	/// unread activity notifications
+	#[serde(default)]
+	pub notifications: u64,
+	/// unread messages
+	#[serde(default)]
+	pub unread_messages: u64,
+	/// unanswered asks and submissions
+	#[serde(default)]
+	pub inbox: u64,
+	/// new posts on the dashboard since it was last seen
+	#[serde(default)]
+	pub posts: u64,
+}
+
+/// One of the user's blogs, as listed in [`UserInfo::blogs`]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct UserBlog {
+	pub name: String,
+	pub title: String,
+	pub url: String,
+	/// including the `t:` prefix
+	pub uuid: String,
+	/// every user has exactly one primary blog, the rest are secondary blogs
+	#[serde(default)]
+	pub primary: bool,
+	pub followers: Option<u64>,
+	/// the user can manage the blog's members, only sent for group blogs
+	pub admin: Option<bool>,
+}
+
+/// The authenticated user's account, see [`Client::user_info`]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct UserInfo {
+	pub name: String,
+	/// number of posts the user has liked
+	pub likes: u64,
+	/// number of blogs the user follows
+	pub following: u64,
+	pub default_post_format: Option<String>,
+	#[serde(default)]
+	pub blogs: Vec<UserBlog>,
+}
+
+/// A blog the user follows, see [`Client::following`]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct FollowedBlog {
+	pub name: String,
+	pub title: String,
+	pub url: String,
+	/// including the `t:` prefix
+	pub uuid: String,
+	pub description: Option<String>,
+	/// seconds from epoch
+	pub updated: u64,
+}
+
+/// A page of the blogs the user follows, most recently followed first
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct FollowingPage {
+	#[serde(default)]
+	pub blogs: Vec<FollowedBlog>,
+	/// total number of blogs the user follows
+	pub total_blogs: u64,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct FollowingOptions {
+	/// number of results to return, 1-20
+	pub limit: Option<u32>,
+	pub offset: Option<u64>,
+}
+
+impl FollowingOptions {
+	/// Returns [`Error::Validation`] if `limit` is out of range.  Called before every request
+	pub fn validate(&self) -> crate::Result<()> {
+		check_limit(self.limit)
+	}
+}
+
+/// How many of something the user can still do before [`Limit::reset_at`]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Limit {
+	/// e.g. "Posts per day"
+	pub description: Option<String>,
+	pub limit: u64,
+	pub remaining: u64,
+	/// seconds from epoch
+	pub reset_at: u64,
+}
+
+/// A cap on API calls, from the `X-Ratelimit-*` headers of a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestLimit {
+	pub limit: u64,
+	pub remaining: u64,
+	/// seconds until `remaining` is back to `limit`
+	pub reset: u64,
+}
+
+impl RequestLimit {
+	/// The `X-Ratelimit-{period}-Limit`, `-Remaining` and `-Reset` headers, or `None` if any is missing or isn't a number
+	fn from_headers(headers: &HeaderMap, period: &str) -> Option<Self> {
+		let header = |name: &str| {
+			headers
+				.get(format!("x-ratelimit-{period}-{name}"))?
+				.to_str()
+				.ok()?
+				.parse()
+				.ok()
+		};
+
+		Some(Self {
+			limit: header("limit")?,
+			remaining: header("remaining")?,
+			reset: header("reset")?,
+		})
+	}
+}
+
+/// The user's quotas, see [`Client::limits`].
+/// A limit Tumblr doesn't report is `None`
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ApiLimits {
+	/// posts per day, including reblogs
+	pub posts: Option<Limit>,
+	/// photos uploaded per day
+	pub photos: Option<Limit>,
+	/// videos uploaded per day
+	pub videos: Option<Limit>,
+	/// total length of videos uploaded per day, in seconds
+	pub video_seconds: Option<Limit>,
+	/// blogs followed per day
+	pub follows: Option<Limit>,
+	/// posts liked per day
+	pub likes: Option<Limit>,
+	/// blogs created per day
+	pub blogs: Option<Limit>,
+	/// API calls per hour for the consumer key
+	#[serde(skip)]
+	pub requests_per_hour: Option<RequestLimit>,
+	/// API calls per day for the consumer key
+	#[serde(skip)]
+	pub requests_per_day: Option<RequestLimit>,
+}
+
+#[derive(Deserialize)]
+struct UserInfoResponse {
+	user: UserInfo,
+}
+
+#[derive(Deserialize)]
+struct LimitsResponse {
+	user: ApiLimits,
+}
+
+impl Client<Authenticated> {
+	/// The user's unread notification, message, and inbox counts
+	pub async fn counts(&self) -> crate::Result<Counts> {
+		self.get("user/counts").await
+	}
+
+	/// The user's account and the blogs they're a member of
+	pub async fn user_info(&self) -> crate::Result<UserInfo> {
+		self.get("user/info")
+			.await
+			.map(|UserInfoResponse { user }| user)
+	}
+
+	/// The user's daily post, upload, follow and like quotas, along with the API call caps from the response's rate limit headers
+	pub async fn limits(&self) -> crate::Result<ApiLimits> {
+		let url: Url = format!("{}/user/limits", self.api_base).parse()?;
+
+		let credentials = &self.state().0;
+
+		// sent directly rather than through `Client::get`, which only keeps the body
+		let res = oauth::ResignableRequest::new(self, self.client.get(url).build()?)
+			.sign_and_send(
+				Some(&credentials.oauth_token),
+				Some(&credentials.oauth_token_secret),
+				None,
+			)
+			.await?;
+
+		let requests_per_hour = RequestLimit::from_headers(res.headers(), "perhour");
+		let requests_per_day = RequestLimit::from_headers(res.headers(), "perday");
+
+		let (_, LimitsResponse { user }) = parse_response(&res.text().await?)?;
+
+		Ok(ApiLimits {
+			requests_per_hour,
+			requests_per_day,
+			..user
+		})
+	}
+
+	/// A page of the blogs the user follows.
+	/// This is the user's list, which can differ from what any one of their blogs follows
+	pub async fn following(&self, options: &FollowingOptions) -> crate::Result<FollowingPage> {
+		options.validate()?;
+
+		self.get(&with_query("user/following".to_owned(), options)?)
+			.await
+	}
+
+	/// Every blog the user follows starting at `options.offset`, fetching pages of `options.limit` as the stream is polled.
+	/// The stream ends after the first error
+	pub fn following_stream(
+		&self,
+		options: FollowingOptions,
+	) -> impl Stream<Item = crate::Result<FollowedBlog>> + '_ {
+		stream::try_unfold(Some(options), move |options| async move {
+			let Some(mut options) = options else {
+				return Ok(None);
+			};
+
+			let page = self.following(&options).await?;
+
+			let offset = options.offset.unwrap_or(0) + page.blogs.len() as u64;
+			let next = (!page.blogs.is_empty() && offset < page.total_blogs).then(|| {
+				options.offset = Some(offset);
+				options
+			});
+
+			Ok::<_, Error>(Some((stream::iter(page.blogs.into_iter().map(Ok)), next)))
+		})
+		.try_flatten()
+	}
+
+	/// The user's primary blog, from [`Client::user_info`], or from the client's cache if
+	/// [`ClientBuilder::blog_info_cache`](crate::ClientBuilder::blog_info_cache) is enabled.
+	/// Returns [`Error::NoPrimaryBlog`] if none of the user's blogs is marked primary
+	pub async fn primary_blog(&self) -> crate::Result<UserBlog> {
+		let cache = self.primary_blog_cache.as_ref();
+
+		if let Some(blog) = cache.and_then(|cache| cache.get("primary")) {
+			return Ok(blog);
+		}
+
+		let blog = self
+			.user_info()
+			.await?
+			.blogs
+			.into_iter()
+			.find(|blog| blog.primary)
+			.ok_or(Error::NoPrimaryBlog)?;
+
+		if let Some(cache) = cache {
+			cache.insert("primary".to_owned(), blog.clone());
+		}
+
+		Ok(blog)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use futures_util::StreamExt;
+	use wiremock::{
+		matchers::{method, path, query_param, query_param_is_missing},
+		Mock, MockServer,
+	};
+
+	use super::*;
+	use crate::client::tests::{authenticated, mock_builder, mock_client, ok_response};
+
+	const USER_INFO: &str = r#"{
+	"user": {
+		"name": "derekg",
+		"likes": 25,
+		"following": 263,
+		"default_post_format": "html",
+		"blogs": [
+			{
+				"name": "derekg-art",
+				"title": "Art",
+				"url": "https://derekg-art.tumblr.com/",
+				"uuid": "t:secondary",
+				"primary": false,
+				"followers": 12
+			},
+			{
+				"name": "derekg",
+				"title": "Derek Gottfrid",
+				"url": "https://derekg.tumblr.com/",
+				"uuid": "t:primary",
+				"primary": true,
+				"followers": 33004929
+			}
+		]
+	}
+}"#;
+
+	#[tokio::test]
+	async fn test_counts() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/user/counts"))
+			.respond_with(ok_response(
+				r#"{
+	"notifications": 12,
+	"unread_messages": 3,
+	"inbox": 1
+}"#,
+			))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let counts = mock_client(&server).counts().await.unwrap();
+
+		assert_eq!(
+			counts,
+			Counts {
+				notifications: 12,
+				unread_messages: 3,
+				inbox: 1,
+				posts: 0,
+			}
+		);
+	}
+
+	#[tokio::test]
+	async fn test_primary_blog() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/user/info"))
+			.respond_with(ok_response(USER_INFO))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let client = authenticated(
+			mock_builder(&server).blog_info_cache(8, std::time::Duration::from_secs(60)),
+		);
+
+		let primary = client.primary_blog().await.unwrap();
+		assert_eq!(primary.name, "derekg");
+		assert_eq!(primary.uuid, "t:primary");
+		assert!(primary.primary);
+
+		// from the cache
+		assert_eq!(client.primary_blog().await.unwrap(), primary);
+	}
+
+	#[tokio::test]
+	async fn test_no_primary_blog() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/user/info"))
+			.respond_with(ok_response(
+				&USER_INFO.replace(r#""primary": true"#, r#""primary": false"#),
+			))
+			.expect(2)
+			.mount(&server)
+			.await;
+
+		let client = mock_client(&server);
+
+		assert_eq!(client.user_info().await.unwrap().blogs.len(), 2);
+		assert!(matches!(
+			client.primary_blog().await,
+			Err(Error::NoPrimaryBlog)
+		));
+	}
+
+	fn followed_blog(name: &str) -> String {
+		format!(
+			r#"{{
+	"name": "{name}",
+	"title": "{name}'s blog",
+	"url": "https://{name}.tumblr.com/",
+	"uuid": "t:{name}",
+	"updated": 1654041600
+}}"#
+		)
+	}
+
+	#[tokio::test]
+	async fn test_following() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/user/following"))
+			.and(query_param("limit", "20"))
+			.respond_with(ok_response(&format!(
+				r#"{{"blogs": [{}, {}], "total_blogs": 2}}"#,
+				followed_blog("staff"),
+				followed_blog("engineering")
+			)))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let page = mock_client(&server)
+			.following(&FollowingOptions {
+				limit: Some(20),
+				..Default::default()
+			})
+			.await
+			.unwrap();
+
+		assert_eq!(page.total_blogs, 2);
+		assert_eq!(
+			page.blogs[1],
+			FollowedBlog {
+				name: "engineering".to_owned(),
+				title: "engineering's blog".to_owned(),
+				url: "https://engineering.tumblr.com/".to_owned(),
+				uuid: "t:engineering".to_owned(),
+				description: None,
+				updated: 1654041600,
+			}
+		);
+	}
+
+	#[tokio::test]
+	async fn test_following_stream() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/user/following"))
+			.and(query_param_is_missing("offset"))
+			.respond_with(ok_response(&format!(
+				r#"{{"blogs": [{}, {}], "total_blogs": 3}}"#,
+				followed_blog("staff"),
+				followed_blog("engineering")
+			)))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		Mock::given(method("GET"))
+			.and(path("/user/following"))
+			.and(query_param("offset", "2"))
+			.respond_with(ok_response(&format!(
+				r#"{{"blogs": [{}], "total_blogs": 3}}"#,
+				followed_blog("changes")
+			)))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let client = mock_client(&server);
+		let names: Vec<_> = client
+			.following_stream(FollowingOptions {
+				limit: Some(2),
+				..Default::default()
+			})
+			.map(|blog| blog.unwrap().name)
+			.collect()
+			.await;
+
+		assert_eq!(names, ["staff", "engineering", "changes"]);
+	}
+
+	#[tokio::test]
+	async fn test_limits() {
+		let server = MockServer::start().await;
+
+		Mock::given(method("GET"))
+			.and(path("/user/limits"))
+			.respond_with(
+				ok_response(
+					r#"{
+	"user": {
+		"posts": {
+			"description": "Posts per day",
+			"limit": 250,
+			"remaining": 247,
+			"reset_at": 1654128000
+		},
+		"follows": {
+			"description": "Follows per day",
+			"limit": 200,
+			"remaining": 200,
+			"reset_at": 1654128000
+		}
+	}
+}"#,
+				)
+				.insert_header("X-Ratelimit-Perhour-Limit", "1000")
+				.insert_header("X-Ratelimit-Perhour-Remaining", "998")
+				.insert_header("X-Ratelimit-Perhour-Reset", "1800")
+				.insert_header("X-Ratelimit-Perday-Limit", "5000")
+				.insert_header("X-Ratelimit-Perday-Remaining", "4990")
+				// not a number, so the daily cap is left out
+				.insert_header("X-Ratelimit-Perday-Reset", "soon"),
+			)
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let limits = mock_client(&server).limits().await.unwrap();
+
+		assert_eq!(
+			limits,
+			ApiLimits {
+				posts: Some(Limit {
+					description: Some("Posts per day".to_owned()),
+					limit: 250,
+					remaining: 247,
+					reset_at: 1654128000,
+				}),
+				follows: Some(Limit {
+					description: Some("Follows per day".to_owned()),
+					limit: 200,
+					remaining: 200,
+					reset_at: 1654128000,
+				}),
+				requests_per_hour: Some(RequestLimit {
+					limit: 1000,
+					remaining: 998,
+					reset: 1800,
+				}),
+				requests_per_day: None,
+				..Default::default()
+			}
+		);
+	}
+}